@@ -9,7 +9,7 @@
 use bevy::{math::Vec2, prelude::*};
 use bevy_asset_loader::prelude::*;
 
-use crate::GameState;
+use crate::{in_gameplay, loading::TrackedCollectionAppExt};
 
 #[derive(Component, Deref, DerefMut)]
 pub struct AnimationTimer(pub Timer);
@@ -55,12 +55,8 @@ pub struct AssetsPlugin;
 
 impl Plugin for AssetsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_collection_to_loading_state::<_, ImageAssets>(GameState::Loading)
-            //.add_systems(OnEnter(GameState::Active), draw_fish)
-            .add_systems(
-                Update,
-                animate_sprite_system.run_if(in_state(GameState::Active)),
-            );
+        app.add_tracked_collection::<ImageAssets>()
+            .add_systems(Update, animate_sprite_system.run_if(in_gameplay));
     }
 }
 
@@ -83,42 +79,3 @@ fn animate_sprite_system(
         }
     }
 }
-
-fn draw_fish(mut commands: Commands, image_assets: Res<ImageAssets>) {
-    // draw single texture from sprite sheet starting at index 0
-    let sprite_size = Vec2::new(10., 10.);
-    commands.spawn((
-        SpriteBundle {
-            sprite: Sprite {
-                custom_size: Some(sprite_size),
-                ..Default::default()
-            },
-            transform: Transform {
-                translation: Vec3::new(-50., 0., 1.),
-                ..Default::default()
-            },
-            //sprite: TextureAtlasSprite::new(0),
-            texture: image_assets.puffer_fish.clone(),
-            ..Default::default()
-        },
-        Name::new("puffer_fish"),
-    ));
-    commands.spawn((
-        SpriteBundle {
-            sprite: Sprite {
-                custom_size: Some(sprite_size), ..Default::default()
-            },
-            transform: Transform {
-                translation: Vec3::new(50., 0., 1.),
-                ..Default::default()
-            },
-            //sprite: TextureAtlasSprite::new(0),
-            texture: image_assets.red_fish.clone(),
-            ..Default::default()
-        }, Name::new("red_fish")))
-        // .insert(AnimationTimer(Timer::from_seconds(
-        //     0.1,
-        //     TimerMode::Repeating,
-        // )))
-    ;
-}
@@ -0,0 +1,46 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::PhysicsSet;
+
+use crate::GameState;
+
+use self::systems::*;
+
+pub mod systems;
+
+pub struct BossPlugin;
+
+impl Plugin for BossPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<BossPhaseEvent>()
+            .add_systems(Update, summon_boss.run_if(in_state(GameState::Playing)))
+            .add_systems(
+                OnTransition {
+                    from: GameState::Playing,
+                    to: GameState::BossFight,
+                },
+                spawn_cthulhu,
+            )
+            .add_systems(
+                Update,
+                (
+                    (update_boss_phase, roar, boss_attack).chain(),
+                    (
+                        bob_head,
+                        move_tentacles,
+                        place_tentacle_parts,
+                        tentacle_grab,
+                    )
+                        .chain(),
+                    (start_boss_death, animate_boss_death).chain(),
+                )
+                    .run_if(in_state(GameState::BossFight)),
+            )
+            // a held whale is dragged after it has moved itself, but before Rapier sees it
+            .add_systems(
+                PostUpdate,
+                hold_whales
+                    .before(PhysicsSet::SyncBackend)
+                    .run_if(in_state(GameState::BossFight)),
+            );
+    }
+}
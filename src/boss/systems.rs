@@ -0,0 +1,622 @@
+use std::f32::consts::{FRAC_PI_2, PI};
+
+use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
+use bevy_rapier2d::prelude::*;
+use rand::prelude::*;
+
+use crate::{
+    assets::ImageAssets,
+    camera::CameraShake,
+    despawn::{Lifetime, RunScoped},
+    health::{DamageEvent, DeathEvent, Hazard, Health},
+    krill::systems::{Krill, KrillBundle, KRILL_MAX_SPEED},
+    player::{Boost, Dying, Whale},
+    settings::Settings,
+    GameState,
+};
+
+// Cthulhu wakes once only this much of the starting swarm is left
+const BOSS_SUMMON_FRACTION: f32 = 0.3;
+const BOSS_POSITION: Vec2 = Vec2::new(0.0, -42.0);
+const BOSS_RADIUS: f32 = 14.0;
+const BOSS_HEALTH: f32 = 300.0;
+const BOSS_INVULNERABILITY: f32 = 0.1;
+const BOSS_CONTACT_DAMAGE: f32 = 15.0;
+const BOSS_COLOR: Color = Color::rgb(0.25, 0.45, 0.3);
+const BOSS_BOB_HEIGHT: f32 = 3.0;
+const BOSS_BOB_SPEED: f32 = 0.8;
+const BOSS_PHASE_TRAUMA: f32 = 0.6;
+const BOSS_DEATH_DURATION: f32 = 3.0;
+const BOSS_DEATH_SINK_SPEED: f32 = 8.0;
+const EYE_RADIUS: f32 = 2.5;
+const EYE_OFFSET: Vec2 = Vec2::new(5.0, 4.0);
+// where the tentacles grow from around the head, in degrees from +x
+const TENTACLE_ANGLES: [f32; 4] = [160.0, 115.0, 65.0, 20.0];
+const TENTACLE_SEGMENTS: usize = 8;
+const TENTACLE_SEGMENT_LENGTH: f32 = 7.0;
+const TENTACLE_WIDTH: f32 = 4.0;
+const TENTACLE_COLOR: Color = Color::rgb(0.2, 0.38, 0.26);
+const TENTACLE_TIP_RADIUS: f32 = 3.0;
+const TENTACLE_TIP_COLOR: Color = Color::rgb(0.55, 0.2, 0.3);
+const TENTACLE_TIP_DAMAGE: f32 = 5.0;
+// how fast a tip can chase wherever it wants to be
+const TENTACLE_TIP_SPEED: f32 = 60.0;
+const TENTACLE_REACH_SPEED: f32 = 110.0;
+const TENTACLE_SWAY: f32 = 12.0;
+const TENTACLE_REACH_TIME: f32 = 2.5;
+const TENTACLE_HOLD_TIME: f32 = 3.0;
+// a held whale that boosts struggles free this many times faster
+const TENTACLE_STRUGGLE_MULTIPLIER: f32 = 3.0;
+const TENTACLE_SQUEEZE_DAMAGE: f32 = 10.0;
+const TENTACLE_SQUEEZE_INTERVAL: f32 = 1.0;
+const FABRIK_ITERATIONS: usize = 4;
+const INK_RADIUS: f32 = 25.0;
+const INK_LIFETIME: f32 = 6.0;
+const INK_DAMAGE: f32 = 3.0;
+const INK_COLOR: Color = Color::rgba(0.05, 0.0, 0.1, 0.85);
+const SUMMONED_KRILL: usize = 40;
+
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum Attack {
+    Grab,
+    InkCloud,
+    SummonKrill,
+}
+
+/// Cthulhu gets meaner as it is hurt.
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default)]
+pub enum BossPhase {
+    #[default]
+    Awakening,
+    Enraged,
+    Desperate,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PhaseTuning {
+    /// seconds between attacks
+    pub attack_interval: f32,
+    pub attacks: &'static [Attack],
+    /// how quickly idle tentacles sway
+    pub sway_speed: f32,
+}
+
+impl BossPhase {
+    pub fn from_health(fraction: f32) -> Self {
+        if fraction > 2.0 / 3.0 {
+            BossPhase::Awakening
+        } else if fraction > 1.0 / 3.0 {
+            BossPhase::Enraged
+        } else {
+            BossPhase::Desperate
+        }
+    }
+
+    pub fn tuning(self) -> PhaseTuning {
+        match self {
+            BossPhase::Awakening => PhaseTuning {
+                attack_interval: 4.0,
+                attacks: &[Attack::Grab],
+                sway_speed: 1.0,
+            },
+            BossPhase::Enraged => PhaseTuning {
+                attack_interval: 3.0,
+                attacks: &[Attack::Grab, Attack::InkCloud],
+                sway_speed: 1.8,
+            },
+            BossPhase::Desperate => PhaseTuning {
+                attack_interval: 2.0,
+                attacks: &[Attack::Grab, Attack::InkCloud, Attack::SummonKrill],
+                sway_speed: 2.6,
+            },
+        }
+    }
+}
+
+/// Sent whenever the boss moves into a new phase.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct BossPhaseEvent {
+    pub phase: BossPhase,
+}
+
+/// The head. It takes the damage; the tentacles only deal it.
+#[derive(Clone, Debug, Component)]
+pub struct Cthulhu {
+    pub home: Vec2,
+    pub phase: BossPhase,
+    pub attack: Timer,
+}
+
+/// Marks every entity that makes up the boss, so it can be cleared away in one go.
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default, Component)]
+pub struct BossPart;
+
+#[derive(Clone, Debug)]
+pub enum TentacleMode {
+    Sway,
+    Reach {
+        target: Entity,
+        timer: Timer,
+    },
+    Hold {
+        whale: Entity,
+        timer: Timer,
+        squeeze: Timer,
+    },
+}
+
+/// A chain of joints posed each frame with FABRIK, so the tip can chase a goal while the root
+/// stays fixed to the head.
+#[derive(Clone, Debug, Component)]
+pub struct Tentacle {
+    /// where the root sits relative to the head's centre
+    pub anchor: Vec2,
+    /// the direction it hangs in while idle
+    pub rest: Vec2,
+    pub sway_offset: f32,
+    pub joints: Vec<Vec2>,
+    pub mode: TentacleMode,
+}
+
+impl Tentacle {
+    pub fn tip(&self) -> Vec2 {
+        self.joints[self.joints.len() - 1]
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Component)]
+pub struct TentacleSegment {
+    pub tentacle: Entity,
+    pub index: usize,
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Component)]
+pub struct TentacleTip {
+    pub tentacle: Entity,
+}
+
+#[derive(Component, Debug, Deref, DerefMut)]
+pub struct BossDying(pub Timer);
+
+pub fn summon_boss(
+    krill_query: Query<(), With<Krill>>,
+    settings: Res<Settings>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let starting = settings.krill_quality.krill_count();
+    let threshold = (starting as f32 * BOSS_SUMMON_FRACTION) as usize;
+    if krill_query.iter().len() <= threshold {
+        next_state.set(GameState::BossFight);
+    }
+}
+
+pub fn spawn_cthulhu(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let phase = BossPhase::default();
+    let eye_mesh = meshes.add(shape::Circle::new(EYE_RADIUS).into());
+    let eye_material = materials.add(ColorMaterial::from(Color::YELLOW));
+    commands
+        .spawn((
+            Name::new("Cthulhu"),
+            Cthulhu {
+                home: BOSS_POSITION,
+                phase,
+                attack: Timer::from_seconds(phase.tuning().attack_interval, TimerMode::Repeating),
+            },
+            BossPart,
+            RunScoped,
+            MaterialMesh2dBundle {
+                mesh: meshes.add(shape::Circle::new(BOSS_RADIUS).into()).into(),
+                // its own material, as hits tint it and dying fades it
+                material: materials.add(ColorMaterial::from(BOSS_COLOR)),
+                transform: Transform::from_translation(BOSS_POSITION.extend(0.5)),
+                ..default()
+            },
+            Health::new(BOSS_HEALTH, BOSS_INVULNERABILITY),
+            Hazard {
+                damage: BOSS_CONTACT_DAMAGE,
+            },
+            Collider::ball(BOSS_RADIUS),
+            RigidBody::KinematicPositionBased,
+        ))
+        .with_children(|parent| {
+            for side in [-1.0, 1.0] {
+                parent.spawn(MaterialMesh2dBundle {
+                    mesh: eye_mesh.clone().into(),
+                    material: eye_material.clone(),
+                    transform: Transform::from_translation(
+                        (EYE_OFFSET * Vec2::new(side, 1.0)).extend(0.1),
+                    ),
+                    ..default()
+                });
+            }
+        });
+
+    let tip_mesh = meshes.add(shape::Circle::new(TENTACLE_TIP_RADIUS).into());
+    let tip_material = materials.add(ColorMaterial::from(TENTACLE_TIP_COLOR));
+    for (i, angle) in TENTACLE_ANGLES.iter().enumerate() {
+        let rest = Vec2::from_angle(angle.to_radians());
+        let anchor = rest * BOSS_RADIUS * 0.8;
+        let root = BOSS_POSITION + anchor;
+        let joints: Vec<Vec2> = (0..=TENTACLE_SEGMENTS)
+            .map(|joint| root + rest * joint as f32 * TENTACLE_SEGMENT_LENGTH)
+            .collect();
+        let tip = joints[TENTACLE_SEGMENTS];
+
+        let tentacle = commands
+            .spawn((
+                Name::new("Tentacle"),
+                BossPart,
+                RunScoped,
+                Tentacle {
+                    anchor,
+                    rest,
+                    sway_offset: i as f32,
+                    joints,
+                    mode: TentacleMode::Sway,
+                },
+            ))
+            .id();
+
+        for index in 0..TENTACLE_SEGMENTS {
+            // thinner towards the tip
+            let width = TENTACLE_WIDTH * (1.0 - 0.6 * index as f32 / TENTACLE_SEGMENTS as f32);
+            commands.spawn((
+                Name::new("Tentacle Segment"),
+                BossPart,
+                RunScoped,
+                TentacleSegment { tentacle, index },
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: TENTACLE_COLOR,
+                        custom_size: Some(Vec2::new(width, TENTACLE_SEGMENT_LENGTH + 1.0)),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(root.extend(0.4)),
+                    ..default()
+                },
+            ));
+        }
+
+        commands.spawn((
+            Name::new("Tentacle Tip"),
+            BossPart,
+            RunScoped,
+            TentacleTip { tentacle },
+            MaterialMesh2dBundle {
+                mesh: tip_mesh.clone().into(),
+                material: tip_material.clone(),
+                transform: Transform::from_translation(tip.extend(0.45)),
+                ..default()
+            },
+            Hazard {
+                damage: TENTACLE_TIP_DAMAGE,
+            },
+            Collider::ball(TENTACLE_TIP_RADIUS),
+            Sensor,
+            RigidBody::KinematicPositionBased,
+        ));
+    }
+}
+
+pub fn update_boss_phase(
+    mut head_query: Query<(&Health, &mut Cthulhu)>,
+    mut phase_events: EventWriter<BossPhaseEvent>,
+) {
+    for (health, mut cthulhu) in &mut head_query {
+        let phase = BossPhase::from_health(health.fraction());
+        if phase != cthulhu.phase {
+            cthulhu.phase = phase;
+            cthulhu.attack =
+                Timer::from_seconds(phase.tuning().attack_interval, TimerMode::Repeating);
+            phase_events.send(BossPhaseEvent { phase });
+        }
+    }
+}
+
+pub fn roar(mut phase_events: EventReader<BossPhaseEvent>, mut shake: ResMut<CameraShake>) {
+    for event in phase_events.read() {
+        info!("Cthulhu enters its {:?} phase", event.phase);
+        shake.add_trauma(BOSS_PHASE_TRAUMA);
+    }
+}
+
+pub fn boss_attack(
+    mut commands: Commands,
+    time: Res<Time>,
+    image_assets: Res<ImageAssets>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut head_query: Query<(&Transform, &mut Cthulhu), Without<BossDying>>,
+    mut tentacle_query: Query<&mut Tentacle>,
+    whale_query: Query<(Entity, &Transform), (With<Whale>, Without<Dying>)>,
+) {
+    let Ok((head_transform, mut cthulhu)) = head_query.get_single_mut() else {
+        return;
+    };
+    if !cthulhu.attack.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let mut rand_gen = thread_rng();
+    let Some((whale, whale_transform)) = whale_query.iter().choose(&mut rand_gen) else {
+        return;
+    };
+    let Some(attack) = cthulhu.phase.tuning().attacks.choose(&mut rand_gen) else {
+        return;
+    };
+    let head = head_transform.translation.truncate();
+    let victim = whale_transform.translation.truncate();
+
+    match attack {
+        Attack::Grab => {
+            // the closest free tentacle lunges for the victim
+            let tentacle = tentacle_query
+                .iter_mut()
+                .filter(|tentacle| matches!(tentacle.mode, TentacleMode::Sway))
+                .min_by(|a, b| {
+                    a.tip()
+                        .distance_squared(victim)
+                        .total_cmp(&b.tip().distance_squared(victim))
+                });
+            if let Some(mut tentacle) = tentacle {
+                tentacle.mode = TentacleMode::Reach {
+                    target: whale,
+                    timer: Timer::from_seconds(TENTACLE_REACH_TIME, TimerMode::Once),
+                };
+            }
+        }
+        Attack::InkCloud => {
+            commands.spawn((
+                Name::new("Ink Cloud"),
+                MaterialMesh2dBundle {
+                    mesh: meshes.add(shape::Circle::new(INK_RADIUS).into()).into(),
+                    material: materials.add(ColorMaterial::from(INK_COLOR)),
+                    // drawn over everything swimming, to blind whoever is inside
+                    transform: Transform::from_translation(victim.extend(5.0)),
+                    ..default()
+                },
+                Hazard { damage: INK_DAMAGE },
+                Collider::ball(INK_RADIUS),
+                Sensor,
+                Lifetime::from_seconds(INK_LIFETIME),
+                RunScoped,
+            ));
+        }
+        Attack::SummonKrill => {
+            // krill burst out of the top of the head, away from the floor
+            for _ in 0..SUMMONED_KRILL {
+                let direction = Vec2::from_angle(rand_gen.gen_range(0.0..PI));
+                let speed = rand_gen.gen_range(KRILL_MAX_SPEED * 0.5..KRILL_MAX_SPEED);
+                commands.spawn(KrillBundle::new(
+                    &image_assets,
+                    head + direction * (BOSS_RADIUS + 2.0),
+                    direction * speed,
+                ));
+            }
+        }
+    }
+}
+
+pub fn bob_head(
+    time: Res<Time>,
+    mut head_query: Query<(&Cthulhu, &mut Transform), Without<BossDying>>,
+) {
+    for (cthulhu, mut transform) in &mut head_query {
+        let bob = (time.elapsed_seconds() * BOSS_BOB_SPEED).sin() * BOSS_BOB_HEIGHT;
+        transform.translation = (cthulhu.home + Vec2::Y * bob).extend(transform.translation.z);
+    }
+}
+
+pub fn move_tentacles(
+    time: Res<Time>,
+    head_query: Query<(&Transform, &Cthulhu)>,
+    mut tentacle_query: Query<&mut Tentacle>,
+    whale_query: Query<&Transform, (With<Whale>, Without<Dying>)>,
+) {
+    let Ok((head_transform, cthulhu)) = head_query.get_single() else {
+        return;
+    };
+    let head = head_transform.translation.truncate();
+    let length = TENTACLE_SEGMENTS as f32 * TENTACLE_SEGMENT_LENGTH;
+    let sway_speed = cthulhu.phase.tuning().sway_speed;
+
+    for mut tentacle in &mut tentacle_query {
+        let tentacle = tentacle.as_mut();
+        let root = head + tentacle.anchor;
+        let sway = (time.elapsed_seconds() * sway_speed + tentacle.sway_offset).sin();
+        let resting =
+            root + tentacle.rest * length * 0.75 + tentacle.rest.perp() * sway * TENTACLE_SWAY;
+
+        let (desired, speed) = match &mut tentacle.mode {
+            TentacleMode::Sway => (resting, TENTACLE_TIP_SPEED),
+            TentacleMode::Reach { target, timer } => match whale_query.get(*target) {
+                Ok(whale) if !timer.tick(time.delta()).finished() => {
+                    (whale.translation.truncate(), TENTACLE_REACH_SPEED)
+                }
+                _ => {
+                    tentacle.mode = TentacleMode::Sway;
+                    (resting, TENTACLE_TIP_SPEED)
+                }
+            },
+            // reel the catch in towards the head
+            TentacleMode::Hold { .. } => (
+                root + tentacle.rest * length * 0.4,
+                TENTACLE_TIP_SPEED * 0.5,
+            ),
+        };
+
+        let tip = tentacle.tip();
+        let goal = tip + (desired - tip).clamp_length_max(speed * time.delta_seconds());
+        fabrik(&mut tentacle.joints, root, goal);
+    }
+}
+
+// Forward And Backward Reaching Inverse Kinematics: drag the chain tip-first to the goal, then
+// root-first back to the root, keeping every segment its own length.
+fn fabrik(joints: &mut [Vec2], root: Vec2, goal: Vec2) {
+    let last = joints.len() - 1;
+    for _ in 0..FABRIK_ITERATIONS {
+        joints[last] = goal;
+        for i in (0..last).rev() {
+            let direction = (joints[i] - joints[i + 1]).normalize_or_zero();
+            joints[i] = joints[i + 1] + direction * TENTACLE_SEGMENT_LENGTH;
+        }
+        joints[0] = root;
+        for i in 1..=last {
+            let direction = (joints[i] - joints[i - 1]).normalize_or_zero();
+            joints[i] = joints[i - 1] + direction * TENTACLE_SEGMENT_LENGTH;
+        }
+    }
+}
+
+pub fn place_tentacle_parts(
+    tentacle_query: Query<&Tentacle>,
+    mut segment_query: Query<(&TentacleSegment, &mut Transform)>,
+    mut tip_query: Query<(&TentacleTip, &mut Transform), Without<TentacleSegment>>,
+) {
+    for (segment, mut transform) in &mut segment_query {
+        let Ok(tentacle) = tentacle_query.get(segment.tentacle) else {
+            continue;
+        };
+        let (start, end) = (
+            tentacle.joints[segment.index],
+            tentacle.joints[segment.index + 1],
+        );
+        let direction = end - start;
+        transform.translation = ((start + end) / 2.0).extend(transform.translation.z);
+        // segment sprites are long along their y axis
+        transform.rotation = Quat::from_rotation_z(direction.y.atan2(direction.x) - FRAC_PI_2);
+    }
+    for (tip, mut transform) in &mut tip_query {
+        if let Ok(tentacle) = tentacle_query.get(tip.tentacle) {
+            transform.translation = tentacle.tip().extend(transform.translation.z);
+        }
+    }
+}
+
+pub fn tentacle_grab(
+    rapier_context: Res<RapierContext>,
+    tip_query: Query<(Entity, &TentacleTip)>,
+    mut tentacle_query: Query<&mut Tentacle>,
+    whale_query: Query<(), (With<Whale>, Without<Dying>)>,
+) {
+    for (tip, tentacle_tip) in &tip_query {
+        let Ok(mut tentacle) = tentacle_query.get_mut(tentacle_tip.tentacle) else {
+            continue;
+        };
+        if !matches!(tentacle.mode, TentacleMode::Reach { .. }) {
+            continue;
+        }
+        let caught = rapier_context
+            .intersections_with(tip)
+            .filter(|(_, _, intersecting)| *intersecting)
+            .map(|(a, b, _)| if a == tip { b } else { a })
+            .find(|other| whale_query.contains(*other));
+        if let Some(whale) = caught {
+            tentacle.mode = TentacleMode::Hold {
+                whale,
+                timer: Timer::from_seconds(TENTACLE_HOLD_TIME, TimerMode::Once),
+                squeeze: Timer::from_seconds(TENTACLE_SQUEEZE_INTERVAL, TimerMode::Repeating),
+            };
+        }
+    }
+}
+
+pub fn hold_whales(
+    time: Res<Time>,
+    mut tentacle_query: Query<(Entity, &mut Tentacle)>,
+    mut whale_query: Query<(&mut Transform, &Boost, Has<Dying>), With<Whale>>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    for (entity, mut tentacle) in &mut tentacle_query {
+        let tip = tentacle.tip();
+        let TentacleMode::Hold {
+            whale,
+            timer,
+            squeeze,
+        } = &mut tentacle.mode
+        else {
+            continue;
+        };
+        let Ok((mut transform, boost, dying)) = whale_query.get_mut(*whale) else {
+            tentacle.mode = TentacleMode::Sway;
+            continue;
+        };
+
+        let struggle = if boost.active {
+            TENTACLE_STRUGGLE_MULTIPLIER
+        } else {
+            1.0
+        };
+        if dying || timer.tick(time.delta().mul_f32(struggle)).finished() {
+            tentacle.mode = TentacleMode::Sway;
+            continue;
+        }
+
+        transform.translation = tip.extend(transform.translation.z);
+        if squeeze.tick(time.delta()).just_finished() {
+            damage_events.send(DamageEvent {
+                target: *whale,
+                amount: TENTACLE_SQUEEZE_DAMAGE,
+                source: Some(entity),
+            });
+        }
+    }
+}
+
+pub fn start_boss_death(
+    mut commands: Commands,
+    mut death_events: EventReader<DeathEvent>,
+    head_query: Query<(), With<Cthulhu>>,
+    tip_query: Query<Entity, With<TentacleTip>>,
+    mut tentacle_query: Query<&mut Tentacle>,
+) {
+    for event in death_events.read() {
+        if head_query.contains(event.entity) {
+            // a dying boss can't hurt anyone, or the last whale could lose a won fight
+            commands
+                .entity(event.entity)
+                .insert(BossDying(Timer::from_seconds(
+                    BOSS_DEATH_DURATION,
+                    TimerMode::Once,
+                )))
+                .remove::<(Hazard, Collider)>();
+            for tip in &tip_query {
+                commands.entity(tip).remove::<(Hazard, Collider)>();
+            }
+            // let go of everyone on the way down
+            for mut tentacle in &mut tentacle_query {
+                tentacle.mode = TentacleMode::Sway;
+            }
+        }
+    }
+}
+
+// The head sinks and fades into the deep, then the whole boss is cleared away and the run won.
+pub fn animate_boss_death(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut head_query: Query<(&mut BossDying, &mut Transform, &Handle<ColorMaterial>)>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    part_query: Query<Entity, With<BossPart>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for (mut dying, mut transform, material) in &mut head_query {
+        dying.tick(time.delta());
+        transform.translation.y -= BOSS_DEATH_SINK_SPEED * time.delta_seconds();
+        if let Some(material) = materials.get_mut(material) {
+            material.color.set_a(1.0 - dying.percent());
+        }
+
+        if dying.just_finished() {
+            for part in &part_query {
+                commands.entity(part).despawn_recursive();
+            }
+            next_state.set(GameState::Victory);
+        }
+    }
+}
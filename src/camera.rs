@@ -0,0 +1,141 @@
+use bevy::{prelude::*, render::camera::ScalingMode};
+use rand::prelude::*;
+
+use crate::{health::DamageTakenEvent, map::LevelBounds, player::Player, settings::Settings};
+
+pub const VIEW_MIN_WIDTH: f32 = 256.;
+pub const VIEW_MIN_HEIGHT: f32 = 144.;
+// trauma added per point of damage taken by a player
+const SHAKE_TRAUMA_PER_DAMAGE: f32 = 0.03;
+const SHAKE_DECAY: f32 = 1.5;
+const SHAKE_MAX_OFFSET: f32 = 6.0;
+// room kept around the outermost whales, in world units
+const FRAMING_MARGIN: f32 = 40.0;
+const FRAMING_SMOOTHING: f32 = 4.0;
+
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraShake>()
+            .add_systems(Startup, setup)
+            .add_systems(
+                Update,
+                (add_damage_trauma, shake_camera, frame_players).chain(),
+            );
+    }
+}
+
+/// The gameplay camera. `focus` and `zoom` are where it is framing, before any shake.
+#[derive(Component, Debug)]
+pub struct MainCamera {
+    pub focus: Vec2,
+    pub zoom: f32,
+}
+
+impl Default for MainCamera {
+    fn default() -> Self {
+        Self {
+            focus: Vec2::ZERO,
+            zoom: 1.0,
+        }
+    }
+}
+
+/// Trauma in `0..=1`; the camera shakes by its square so small hits stay subtle.
+#[derive(Resource, Debug, Default)]
+pub struct CameraShake {
+    pub trauma: f32,
+    offset: Vec2,
+}
+
+impl CameraShake {
+    pub fn add_trauma(&mut self, trauma: f32) {
+        self.trauma = (self.trauma + trauma).min(1.0);
+    }
+}
+
+fn setup(mut commands: Commands) {
+    let mut camera = Camera2dBundle::default();
+    camera.projection.scaling_mode = ScalingMode::AutoMin {
+        min_width: VIEW_MIN_WIDTH,
+        min_height: VIEW_MIN_HEIGHT,
+    };
+    commands.spawn((camera, MainCamera::default()));
+}
+
+fn add_damage_trauma(
+    mut damage_taken_events: EventReader<DamageTakenEvent>,
+    player_query: Query<(), With<Player>>,
+    mut shake: ResMut<CameraShake>,
+) {
+    for event in damage_taken_events.read() {
+        if player_query.contains(event.target) {
+            shake.add_trauma(event.amount * SHAKE_TRAUMA_PER_DAMAGE);
+        }
+    }
+}
+
+fn shake_camera(time: Res<Time>, settings: Res<Settings>, mut shake: ResMut<CameraShake>) {
+    // with shake turned off in the settings, trauma is dropped as soon as it arrives
+    if !settings.camera_shake {
+        shake.trauma = 0.0;
+    }
+    if shake.trauma <= 0.0 {
+        shake.offset = Vec2::ZERO;
+        return;
+    }
+
+    let mut rand_gen = thread_rng();
+    let strength = shake.trauma * shake.trauma * SHAKE_MAX_OFFSET;
+    shake.offset = Vec2::new(
+        rand_gen.gen_range(-1.0..=1.0),
+        rand_gen.gen_range(-1.0..=1.0),
+    ) * strength;
+    shake.trauma = (shake.trauma - SHAKE_DECAY * time.delta_seconds()).max(0.0);
+}
+
+// Keeps every whale in view, zooming out as they spread apart, and keeps the view inside the
+// level wherever the level is big enough to allow it.
+fn frame_players(
+    time: Res<Time>,
+    shake: Res<CameraShake>,
+    level_bounds: Res<LevelBounds>,
+    player_query: Query<&Transform, (With<Player>, Without<MainCamera>)>,
+    mut camera_query: Query<(&mut MainCamera, &mut Transform, &mut OrthographicProjection)>,
+) {
+    let whales = player_query
+        .iter()
+        .map(|transform| Rect::from_center_size(transform.translation.xy(), Vec2::ZERO))
+        .reduce(|a, b| a.union(b));
+
+    for (mut camera, mut transform, mut projection) in &mut camera_query {
+        if let Some(whales) = whales {
+            let framed = whales.inset(FRAMING_MARGIN);
+            let zoom = (framed.width() / VIEW_MIN_WIDTH)
+                .max(framed.height() / VIEW_MIN_HEIGHT)
+                .max(1.0);
+
+            let half_view = Vec2::new(VIEW_MIN_WIDTH, VIEW_MIN_HEIGHT) * zoom / 2.0;
+            let allowed = level_bounds.rect;
+            let clamp_axis = |value: f32, min: f32, max: f32, half: f32| {
+                if max - min > half * 2.0 {
+                    value.clamp(min + half, max - half)
+                } else {
+                    (min + max) / 2.0
+                }
+            };
+            let focus = Vec2::new(
+                clamp_axis(framed.center().x, allowed.min.x, allowed.max.x, half_view.x),
+                clamp_axis(framed.center().y, allowed.min.y, allowed.max.y, half_view.y),
+            );
+
+            let t = (FRAMING_SMOOTHING * time.delta_seconds()).min(1.0);
+            camera.focus = camera.focus.lerp(focus, t);
+            camera.zoom += (zoom - camera.zoom) * t;
+        }
+
+        projection.scale = camera.zoom;
+        transform.translation = (camera.focus + shake.offset).extend(transform.translation.z);
+    }
+}
@@ -0,0 +1,429 @@
+use std::{collections::VecDeque, str::FromStr, sync::Arc};
+
+use bevy::{input::InputSystem, prelude::*};
+use bevy_inspector_egui::{
+    bevy_egui::{EguiContexts, EguiPlugin},
+    egui,
+};
+
+use crate::{menu::ResumeState, GameState};
+
+const TOGGLE_KEY: KeyCode = KeyCode::Grave;
+const MAX_LOG_LINES: usize = 200;
+const LOG_HEIGHT: f32 = 200.0;
+const INPUT_ID: &str = "console_input";
+const STATE_NAMES: [&str; 8] = [
+    "menu",
+    "playing",
+    "boss",
+    "paused",
+    "gameover",
+    "victory",
+    "leaderboard",
+    "settings",
+];
+
+/// A drop-down console, opened with the key left of 1, that runs the commands plugins register
+/// with [`AddConsoleCommand`].
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<EguiPlugin>() {
+            app.add_plugins(EguiPlugin);
+        }
+        app.init_resource::<Console>()
+            .init_resource::<ConsoleCommands>()
+            .add_console_command(HelpCommand)
+            .add_console_command(ClearCommand)
+            .add_console_command(StateCommand)
+            .add_console_command(TimescaleCommand)
+            .add_systems(
+                PreUpdate,
+                (toggle_console, swallow_keyboard.run_if(console_open))
+                    .chain()
+                    .after(InputSystem),
+            )
+            .add_systems(Update, (console_panel, run_commands).chain());
+    }
+}
+
+/// Something that can be typed into the console.
+pub trait ConsoleCommand: Send + Sync + 'static {
+    /// the word that runs it
+    fn name(&self) -> &'static str;
+
+    /// what follows the name, shown by `help` and while typing
+    fn usage(&self) -> &'static str {
+        ""
+    }
+
+    /// words the first argument can be, offered by autocomplete
+    fn arguments(&self) -> &[&'static str] {
+        &[]
+    }
+
+    /// Runs with the words typed after the name. Either message is printed to the console.
+    fn run(&self, args: &[&str], world: &mut World) -> Result<String, String>;
+}
+
+pub trait AddConsoleCommand {
+    fn add_console_command(&mut self, command: impl ConsoleCommand) -> &mut Self;
+}
+
+impl AddConsoleCommand for App {
+    fn add_console_command(&mut self, command: impl ConsoleCommand) -> &mut Self {
+        // plugins may register commands before the console plugin is added
+        let mut commands = self
+            .world
+            .get_resource_or_insert_with(ConsoleCommands::default);
+        commands.0.push(Arc::new(command));
+        commands.0.sort_by_key(|command| command.name());
+        self
+    }
+}
+
+/// Every registered command, sorted by name.
+#[derive(Resource, Default)]
+pub struct ConsoleCommands(Vec<Arc<dyn ConsoleCommand>>);
+
+impl ConsoleCommands {
+    pub fn get(&self, name: &str) -> Option<&Arc<dyn ConsoleCommand>> {
+        self.0.iter().find(|command| command.name() == name)
+    }
+
+    /// How many characters of `input` the last word takes, and what it could be completed to.
+    fn completions(&self, input: &str) -> (usize, Vec<&'static str>) {
+        let words: Vec<&str> = input.split_whitespace().collect();
+        let new_word = input.is_empty() || input.ends_with(' ');
+        let partial = if new_word { "" } else { words[words.len() - 1] };
+        let candidates: Vec<&'static str> = match (words.len(), new_word) {
+            (0, _) | (1, false) => self.0.iter().map(|command| command.name()).collect(),
+            (1, true) | (2, false) => self
+                .get(words[0])
+                .map(|command| command.arguments().to_vec())
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        };
+        let candidates = candidates
+            .into_iter()
+            .filter(|candidate| candidate.starts_with(partial))
+            .collect();
+        (partial.len(), candidates)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum LineKind {
+    Input,
+    Output,
+    Error,
+}
+
+impl LineKind {
+    fn color(self) -> egui::Color32 {
+        match self {
+            LineKind::Input => egui::Color32::GRAY,
+            LineKind::Output => egui::Color32::WHITE,
+            LineKind::Error => egui::Color32::LIGHT_RED,
+        }
+    }
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct Console {
+    pub open: bool,
+    input: String,
+    log: VecDeque<(LineKind, String)>,
+    /// submitted lines, oldest first
+    history: Vec<String>,
+    /// the history entry shown while browsing with the arrow keys
+    browsing: Option<usize>,
+    /// lines waiting to be run with world access
+    pending: Vec<String>,
+}
+
+impl Console {
+    fn print(&mut self, kind: LineKind, text: impl Into<String>) {
+        for line in text.into().lines() {
+            if self.log.len() == MAX_LOG_LINES {
+                self.log.pop_front();
+            }
+            self.log.push_back((kind, line.to_string()));
+        }
+    }
+
+    fn browse(&mut self, back: bool) {
+        if self.history.is_empty() {
+            return;
+        }
+        let last = self.history.len() - 1;
+        self.browsing = match (self.browsing, back) {
+            (None, true) => Some(last),
+            (None, false) => None,
+            (Some(index), true) => Some(index.saturating_sub(1)),
+            (Some(index), false) if index < last => Some(index + 1),
+            (Some(_), false) => None,
+        };
+        self.input = self
+            .browsing
+            .map_or_else(String::new, |index| self.history[index].clone());
+    }
+}
+
+/// Parses the argument at `index`, naming it as `name` if it is missing or malformed.
+pub fn parse_arg<T: FromStr>(args: &[&str], index: usize, name: &str) -> Result<T, String> {
+    let arg = args.get(index).ok_or_else(|| format!("missing {name}"))?;
+    arg.parse()
+        .map_err(|_| format!("{arg} is not a valid {name}"))
+}
+
+/// Fails unless a run is in progress, for commands that act on its entities.
+pub fn require_run(world: &World) -> Result<(), String> {
+    match world.resource::<State<GameState>>().get() {
+        GameState::Playing | GameState::BossFight => Ok(()),
+        _ => Err("start a run first".to_string()),
+    }
+}
+
+fn describe(command: &dyn ConsoleCommand) -> String {
+    format!("{} {}", command.name(), command.usage())
+        .trim_end()
+        .to_string()
+}
+
+fn console_open(console: Res<Console>) -> bool {
+    console.open
+}
+
+fn toggle_console(keyboard_input: Res<Input<KeyCode>>, mut console: ResMut<Console>) {
+    if keyboard_input.just_pressed(TOGGLE_KEY) {
+        console.open = !console.open;
+    }
+}
+
+// Typing into the console shouldn't also steer the whale; egui reads its own input events.
+fn swallow_keyboard(mut keyboard_input: ResMut<Input<KeyCode>>) {
+    keyboard_input.reset_all();
+}
+
+fn console_panel(
+    mut contexts: EguiContexts,
+    mut console: ResMut<Console>,
+    commands: Res<ConsoleCommands>,
+) {
+    if !console.open {
+        return;
+    }
+    let console = &mut *console;
+    let ctx = contexts.ctx_mut();
+
+    egui::TopBottomPanel::top("console").show(ctx, |ui| {
+        egui::ScrollArea::vertical()
+            .max_height(LOG_HEIGHT)
+            .auto_shrink([false, false])
+            .stick_to_bottom(true)
+            .show(ui, |ui| {
+                for (kind, line) in &console.log {
+                    ui.colored_label(kind.color(), line);
+                }
+            });
+        ui.separator();
+
+        // taken before the text box sees them, which would move focus or the cursor
+        let (tab, up, down) = ui.input_mut(|input| {
+            (
+                input.consume_key(egui::Modifiers::NONE, egui::Key::Tab),
+                input.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+                input.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+            )
+        });
+        if tab {
+            let (partial, candidates) = commands.completions(&console.input);
+            if let Some(first) = candidates.first() {
+                // complete as far as every candidate agrees
+                let common = candidates.iter().fold(first.len(), |common, candidate| {
+                    first
+                        .chars()
+                        .zip(candidate.chars())
+                        .take(common)
+                        .take_while(|(a, b)| a == b)
+                        .count()
+                });
+                console.input.truncate(console.input.len() - partial);
+                console.input.push_str(&first[..common]);
+                if candidates.len() == 1 {
+                    console.input.push(' ');
+                }
+            }
+        }
+        if up || down {
+            console.browse(up);
+        }
+
+        let input_id = egui::Id::new(INPUT_ID);
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut console.input)
+                .id(input_id)
+                .desired_width(f32::INFINITY)
+                .lock_focus(true)
+                .hint_text("type help for a list of commands"),
+        );
+        // the key that opened the console may have been typed into it
+        console.input.retain(|c| c != '`');
+        if tab || up || down {
+            if let Some(mut state) = egui::TextEdit::load_state(ui.ctx(), input_id) {
+                let end = egui::text::CCursor::new(console.input.chars().count());
+                state.set_ccursor_range(Some(egui::text::CCursorRange::one(end)));
+                state.store(ui.ctx(), input_id);
+            }
+        }
+
+        if response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter)) {
+            let line = console.input.trim().to_string();
+            console.input.clear();
+            console.browsing = None;
+            if !line.is_empty() {
+                console.print(LineKind::Input, format!("> {line}"));
+                console.history.push(line.clone());
+                console.pending.push(line);
+            }
+        }
+        response.request_focus();
+
+        if !console.input.is_empty() {
+            let (_, candidates) = commands.completions(&console.input);
+            let usage = console
+                .input
+                .split_whitespace()
+                .next()
+                .and_then(|name| commands.get(name))
+                .map(|command| describe(command.as_ref()));
+            // the usage once a command is named, then whatever the current word could become
+            ui.horizontal(|ui| {
+                if let Some(usage) = usage {
+                    ui.weak(usage);
+                }
+                ui.label(candidates.join("  "));
+            });
+        }
+    });
+}
+
+fn run_commands(world: &mut World) {
+    let pending = std::mem::take(&mut world.resource_mut::<Console>().pending);
+    for line in pending {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let Some((name, args)) = words.split_first() else {
+            continue;
+        };
+        let command = world.resource::<ConsoleCommands>().get(name).cloned();
+        let result = match command {
+            Some(command) => command.run(args, world),
+            None => Err(format!("unknown command {name}, try help")),
+        };
+
+        let mut console = world.resource_mut::<Console>();
+        match result {
+            Ok(output) if !output.is_empty() => console.print(LineKind::Output, output),
+            Ok(_) => {}
+            Err(error) => console.print(LineKind::Error, error),
+        }
+    }
+}
+
+struct HelpCommand;
+
+impl ConsoleCommand for HelpCommand {
+    fn name(&self) -> &'static str {
+        "help"
+    }
+
+    fn run(&self, _args: &[&str], world: &mut World) -> Result<String, String> {
+        let commands = world.resource::<ConsoleCommands>();
+        Ok(commands
+            .0
+            .iter()
+            .map(|command| describe(command.as_ref()))
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+}
+
+struct ClearCommand;
+
+impl ConsoleCommand for ClearCommand {
+    fn name(&self) -> &'static str {
+        "clear"
+    }
+
+    fn run(&self, _args: &[&str], world: &mut World) -> Result<String, String> {
+        world.resource_mut::<Console>().log.clear();
+        Ok(String::new())
+    }
+}
+
+struct StateCommand;
+
+impl ConsoleCommand for StateCommand {
+    fn name(&self) -> &'static str {
+        "state"
+    }
+
+    fn usage(&self) -> &'static str {
+        "<state>"
+    }
+
+    fn arguments(&self) -> &[&'static str] {
+        &STATE_NAMES
+    }
+
+    fn run(&self, args: &[&str], world: &mut World) -> Result<String, String> {
+        let name = *args.first().ok_or("missing state")?;
+        let state = match name {
+            "menu" => GameState::MainMenu,
+            "playing" => GameState::Playing,
+            "boss" => GameState::BossFight,
+            "paused" => GameState::Paused,
+            "gameover" => GameState::GameOver,
+            "victory" => GameState::Victory,
+            "leaderboard" => GameState::Leaderboard,
+            "settings" => GameState::Settings,
+            _ => return Err(format!("no state called {name}")),
+        };
+
+        if state == GameState::Paused {
+            // pausing needs to know where to resume to
+            let current = world.resource::<State<GameState>>().get().clone();
+            if !matches!(current, GameState::Playing | GameState::BossFight) {
+                return Err("only a run can be paused".to_string());
+            }
+            world.resource_mut::<ResumeState>().0 = current;
+        }
+        world.resource_mut::<NextState<GameState>>().set(state);
+        Ok(format!("entering {name}"))
+    }
+}
+
+struct TimescaleCommand;
+
+impl ConsoleCommand for TimescaleCommand {
+    fn name(&self) -> &'static str {
+        "timescale"
+    }
+
+    fn usage(&self) -> &'static str {
+        "<scale>"
+    }
+
+    fn run(&self, args: &[&str], world: &mut World) -> Result<String, String> {
+        let scale: f32 = parse_arg(args, 0, "scale")?;
+        if !scale.is_finite() || scale < 0.0 {
+            return Err("scale must be zero or more".to_string());
+        }
+        world
+            .resource_mut::<Time<Virtual>>()
+            .set_relative_speed(scale);
+        Ok(format!("time runs at {scale}x"))
+    }
+}
@@ -0,0 +1,87 @@
+use bevy::prelude::*;
+
+use crate::{map::LevelBounds, GameState};
+
+pub struct DespawnPlugin;
+
+impl Plugin for DespawnPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::MainMenu), despawn_run)
+            .add_systems(
+                Update,
+                (
+                    despawn_state_scoped.run_if(state_changed::<GameState>()),
+                    despawn_expired,
+                    despawn_outside_bounds,
+                ),
+            );
+    }
+}
+
+/// Despawns the entity, and its children, when the timer finishes.
+#[derive(Component, Debug, Deref, DerefMut)]
+pub struct Lifetime(pub Timer);
+
+impl Lifetime {
+    pub fn from_seconds(seconds: f32) -> Self {
+        Self(Timer::from_seconds(seconds, TimerMode::Once))
+    }
+}
+
+/// Despawns the entity, and its children, once it is more than `margin` outside the level.
+#[derive(Component, Debug, Default, Clone, Copy)]
+pub struct DespawnOutsideBounds {
+    pub margin: f32,
+}
+
+/// Part of a run: cleared away when the game returns to the main menu, so the next run starts
+/// from a fresh level.
+#[derive(Component, Debug, Default, Clone, Copy)]
+pub struct RunScoped;
+
+/// Despawned, with its children, as soon as the game leaves the given state.
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub struct StateScoped(pub GameState);
+
+fn despawn_run(mut commands: Commands, query: Query<Entity, With<RunScoped>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn despawn_state_scoped(
+    mut commands: Commands,
+    state: Res<State<GameState>>,
+    query: Query<(Entity, &StateScoped)>,
+) {
+    for (entity, scope) in &query {
+        if scope.0 != *state.get() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn despawn_expired(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Lifetime)>,
+) {
+    for (entity, mut lifetime) in &mut query {
+        if lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn despawn_outside_bounds(
+    mut commands: Commands,
+    level_bounds: Res<LevelBounds>,
+    query: Query<(Entity, &GlobalTransform, &DespawnOutsideBounds)>,
+) {
+    for (entity, transform, despawn) in &query {
+        let bounds = level_bounds.rect.inset(despawn.margin);
+        if !bounds.contains(transform.translation().xy()) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -1,16 +1,16 @@
 use bevy::prelude::*;
 
-use self::systems::{setup, update_score, DisplayData, DisplayEvent};
-use crate::GameState;
+use self::systems::{reset_score, update_score, DisplayData, DisplayEvent};
+use crate::START_RUN;
 
-mod systems;
+pub mod systems;
 
 pub struct DisplayPlugin;
 
 impl Plugin for DisplayPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(DisplayData { total_score: 0 })
-            .add_systems(OnEnter(GameState::Active), setup)
+            .add_systems(START_RUN, reset_score)
             .add_event::<DisplayEvent>()
             .add_systems(Update, update_score);
     }
@@ -5,14 +5,13 @@ pub fn how_to_use(
     input: Res<Input<KeyCode>>,
     ) {
     if input.pressed(KeyCode::E) {
-        score_event.send(DisplayEvent { points: 1 });
+        score_event.send(DisplayEvent { points: 1, player: None });
     }
 }***********************************/
 
 use bevy::prelude::*;
 
-#[derive(Component, Debug, Default)]
-pub struct ScoreText;
+use crate::player::{Player, Score};
 
 #[derive(Resource, Debug, Default)]
 pub struct DisplayData {
@@ -22,43 +21,26 @@ pub struct DisplayData {
 #[derive(Event, Default)]
 pub struct DisplayEvent {
     pub points: usize,
-}
-
-pub fn setup(mut commands: Commands) {
-    commands.spawn((
-        TextBundle::from_sections([
-            TextSection::new(
-                "Score: ",
-                TextStyle {
-                    font_size: 25.0,
-                    ..default()
-                },
-            ),
-            TextSection::from_style(TextStyle {
-                font_size: 25.0,
-                ..default()
-            }),
-        ])
-        .with_text_alignment(TextAlignment::Center)
-        .with_style(Style {
-            position_type: PositionType::Absolute,
-            bottom: Val::Px(5.0),
-            right: Val::Px(5.0),
-            ..default()
-        }),
-        ScoreText,
-    ));
+    /// the player whale to credit, on top of the shared total
+    pub player: Option<Entity>,
 }
 
 pub fn update_score(
     mut score_events: EventReader<DisplayEvent>,
-    mut query: Query<&mut Text, With<ScoreText>>,
+    mut player_query: Query<&mut Score, With<Player>>,
     mut display_data: ResMut<DisplayData>,
 ) {
-    for mut score in &mut query {
-        for event in score_events.read() {
-            display_data.total_score += event.points;
-            score.sections[1].value = format!("{}", display_data.total_score);
+    for event in score_events.read() {
+        display_data.total_score += event.points;
+        if let Some(mut score) = event
+            .player
+            .and_then(|player| player_query.get_mut(player).ok())
+        {
+            score.0 += event.points;
         }
     }
 }
+
+pub fn reset_score(mut display_data: ResMut<DisplayData>) {
+    display_data.total_score = 0;
+}
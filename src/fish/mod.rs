@@ -0,0 +1,32 @@
+use bevy::prelude::*;
+
+use crate::in_gameplay;
+
+use self::systems::*;
+
+pub mod systems;
+
+pub struct FishPlugin;
+
+impl Plugin for FishPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Species>()
+            .register_type::<FishSpawnRules>()
+            .init_resource::<FishSpawnRules>()
+            .add_event::<FishKilledEvent>()
+            .add_systems(
+                Update,
+                (
+                    spawn_fish,
+                    fish_think,
+                    fish_steer,
+                    fish_face_velocity,
+                    puffer_inflate,
+                    fish_eat_krill,
+                    fish_die,
+                )
+                    .chain()
+                    .run_if(in_gameplay),
+            );
+    }
+}
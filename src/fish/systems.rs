@@ -0,0 +1,453 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use rand::prelude::*;
+
+use crate::{
+    assets::ImageAssets,
+    despawn::RunScoped,
+    health::{DeathEvent, Hazard, Health},
+    krill::systems::{EatenKrill, Krill},
+    map::{BOTTOM_BORDER, LEFT_BORDER, RIGHT_BORDER, TOP_BORDER},
+    physics::{KinematicAcceleration, KinematicVelocity},
+    player::{KrillEatenEvent, Whale},
+    sonar::Concealable,
+};
+
+const FISH_THINK_INTERVAL: f32 = 0.5;
+// how hard fish turn back once they are this close to the krill borders
+const FISH_WALL_MARGIN: f32 = 15.;
+const FISH_WANDER_JITTER: f32 = 0.6;
+const PUFFER_INFLATED_SCALE: f32 = 1.8;
+const PUFFER_DEFLATE_DELAY: f32 = 2.;
+
+/// What a species will eat.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum Diet {
+    Nothing,
+    Krill,
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Hash, Default, Component, Reflect)]
+#[reflect(Component)]
+pub enum Species {
+    Puffer,
+    Red,
+    #[default]
+    Blue,
+    Green,
+}
+
+/// Everything that sets one species apart from another.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SpeciesDef {
+    pub name: &'static str,
+    pub size: f32,
+    pub max_speed: f32,
+    /// the largest steering acceleration
+    pub max_force: f32,
+    pub sight: f32,
+    pub diet: Diet,
+    /// chance, each time it thinks, that a fish goes after a whale it can see
+    pub aggression: f32,
+    /// damage dealt to a whale on contact; pufferfish only hurt while inflated
+    pub contact_damage: f32,
+    pub health: f32,
+}
+
+impl Species {
+    pub fn def(self) -> SpeciesDef {
+        match self {
+            Species::Puffer => SpeciesDef {
+                name: "Puffer Fish",
+                size: 10.,
+                max_speed: 15.,
+                max_force: 20.,
+                sight: 30.,
+                diet: Diet::Nothing,
+                aggression: 0.,
+                contact_damage: 20.,
+                health: 30.,
+            },
+            Species::Red => SpeciesDef {
+                name: "Red Fish",
+                size: 9.,
+                max_speed: 45.,
+                max_force: 80.,
+                sight: 70.,
+                diet: Diet::Krill,
+                aggression: 0.6,
+                contact_damage: 5.,
+                health: 20.,
+            },
+            Species::Blue => SpeciesDef {
+                name: "Blue Fish",
+                size: 8.,
+                max_speed: 40.,
+                max_force: 70.,
+                sight: 60.,
+                diet: Diet::Krill,
+                aggression: 0.,
+                contact_damage: 0.,
+                health: 10.,
+            },
+            Species::Green => SpeciesDef {
+                name: "Green Fish",
+                size: 8.,
+                max_speed: 30.,
+                max_force: 60.,
+                sight: 50.,
+                diet: Diet::Krill,
+                aggression: 0.2,
+                contact_damage: 0.,
+                health: 10.,
+            },
+        }
+    }
+
+    pub fn sprite(self, image_assets: &ImageAssets) -> Handle<Image> {
+        match self {
+            Species::Puffer => image_assets.puffer_fish.clone(),
+            Species::Red => image_assets.red_fish.clone(),
+            Species::Blue => image_assets.blue_fish.clone(),
+            Species::Green => image_assets.green_fish.clone(),
+        }
+    }
+}
+
+/// How often fish arrive, and how many of each there may be at once.
+#[derive(Resource, Debug, Clone, Reflect)]
+#[reflect(Resource)]
+pub struct FishSpawnRules {
+    pub timer: Timer,
+    pub max_total: usize,
+    /// `(species, weight, max alive)`
+    pub species: Vec<(Species, f32, usize)>,
+}
+
+impl Default for FishSpawnRules {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(4., TimerMode::Repeating),
+            max_total: 8,
+            species: vec![
+                (Species::Puffer, 1., 2),
+                (Species::Red, 1., 2),
+                (Species::Blue, 2., 3),
+                (Species::Green, 2., 3),
+            ],
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Behavior {
+    Wander,
+    Hunt(Entity),
+    Harass(Entity),
+}
+
+#[derive(Clone, Debug, Component)]
+pub struct FishBrain {
+    pub behavior: Behavior,
+    pub think: Timer,
+}
+
+/// Puffs up, and becomes dangerous, when a whale comes close.
+#[derive(Clone, Debug, Component)]
+pub struct Inflatable {
+    pub inflated: bool,
+    pub deflate: Timer,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug, Default, Component)]
+pub struct Fish;
+
+#[derive(Bundle)]
+pub struct FishBundle {
+    fish: Fish,
+    species: Species,
+    name: Name,
+    brain: FishBrain,
+    sprite: SpriteBundle,
+    velocity: KinematicVelocity,
+    acceleration: KinematicAcceleration,
+    rigid_body: RigidBody,
+    collider: Collider,
+    sensor: Sensor,
+    health: Health,
+    concealable: Concealable,
+    run_scoped: RunScoped,
+}
+
+pub fn spawn_fish(
+    mut commands: Commands,
+    time: Res<Time>,
+    image_assets: Res<ImageAssets>,
+    mut rules: ResMut<FishSpawnRules>,
+    fish_query: Query<&Species, With<Fish>>,
+) {
+    if !rules.timer.tick(time.delta()).just_finished() || fish_query.iter().len() >= rules.max_total
+    {
+        return;
+    }
+
+    let alive = |species: Species| fish_query.iter().filter(|s| **s == species).count();
+    let candidates: Vec<_> = rules
+        .species
+        .iter()
+        .filter(|(species, _, max)| alive(*species) < *max)
+        .collect();
+    let mut rand_gen = thread_rng();
+    let Ok(&&(species, _, _)) = candidates.choose_weighted(&mut rand_gen, |(_, weight, _)| *weight)
+    else {
+        return;
+    };
+
+    // fish swim in from whichever side wall they spawn against
+    let from_left = rand_gen.gen_bool(0.5);
+    let x = if from_left { LEFT_BORDER } else { RIGHT_BORDER };
+    let y = rand_gen.gen_range(BOTTOM_BORDER..TOP_BORDER);
+    let heading = if from_left { Vec2::X } else { Vec2::NEG_X };
+    let def = species.def();
+
+    let mut entity = commands.spawn(FishBundle {
+        fish: Fish,
+        species,
+        name: Name::new(def.name),
+        brain: FishBrain {
+            behavior: Behavior::Wander,
+            think: Timer::from_seconds(FISH_THINK_INTERVAL, TimerMode::Repeating),
+        },
+        sprite: SpriteBundle {
+            sprite: Sprite {
+                custom_size: Some(Vec2::splat(def.size)),
+                // the fish pack is drawn facing left
+                flip_x: true,
+                ..Default::default()
+            },
+            texture: species.sprite(&image_assets),
+            transform: Transform::from_translation(Vec3::new(x, y, 1.)),
+            ..Default::default()
+        },
+        velocity: KinematicVelocity::linear(heading * def.max_speed * 0.5),
+        acceleration: KinematicAcceleration::default(),
+        rigid_body: RigidBody::KinematicPositionBased,
+        collider: Collider::ball(def.size / 2.),
+        sensor: Sensor,
+        health: Health::new(def.health, 0.),
+        concealable: Concealable,
+        run_scoped: RunScoped,
+    });
+    if species == Species::Puffer {
+        entity.insert(Inflatable {
+            inflated: false,
+            deflate: Timer::from_seconds(PUFFER_DEFLATE_DELAY, TimerMode::Once),
+        });
+    } else if def.contact_damage > 0. {
+        entity.insert(Hazard {
+            damage: def.contact_damage,
+        });
+    }
+}
+
+pub fn fish_think(
+    time: Res<Time>,
+    mut fish_query: Query<(&Species, &Transform, &mut FishBrain), With<Fish>>,
+    player_query: Query<(Entity, &Transform), With<Whale>>,
+    krill_query: Query<(Entity, &Transform), With<Krill>>,
+) {
+    let mut rand_gen = thread_rng();
+    let nearest = |position: Vec2, sight: f32, mut candidates: Vec<(Entity, Vec2)>| {
+        candidates.retain(|(_, other)| other.distance(position) < sight);
+        candidates
+            .into_iter()
+            .min_by(|(_, a), (_, b)| a.distance(position).total_cmp(&b.distance(position)))
+            .map(|(entity, _)| entity)
+    };
+    let whales: Vec<_> = player_query
+        .iter()
+        .map(|(entity, transform)| (entity, transform.translation.xy()))
+        .collect();
+
+    for (species, transform, mut brain) in fish_query.iter_mut() {
+        if !brain.think.tick(time.delta()).just_finished() {
+            continue;
+        }
+        let def = species.def();
+        let position = transform.translation.xy();
+
+        let harass = rand_gen.gen::<f32>() < def.aggression;
+        let whale = harass
+            .then(|| nearest(position, def.sight, whales.clone()))
+            .flatten();
+        let krill = (def.diet == Diet::Krill)
+            .then(|| {
+                let krill = krill_query
+                    .iter()
+                    .map(|(entity, transform)| (entity, transform.translation.xy()))
+                    .collect();
+                nearest(position, def.sight, krill)
+            })
+            .flatten();
+
+        brain.behavior = match (whale, krill) {
+            (Some(whale), _) => Behavior::Harass(whale),
+            (None, Some(krill)) => Behavior::Hunt(krill),
+            (None, None) => Behavior::Wander,
+        };
+    }
+}
+
+pub fn fish_steer(
+    mut fish_query: Query<
+        (
+            &Species,
+            &Transform,
+            &FishBrain,
+            &KinematicVelocity,
+            &mut KinematicAcceleration,
+            Option<&Inflatable>,
+        ),
+        With<Fish>,
+    >,
+    target_query: Query<&Transform, Without<Fish>>,
+) {
+    let mut rand_gen = thread_rng();
+    for (species, transform, brain, velocity, mut acceleration, inflatable) in fish_query.iter_mut()
+    {
+        let def = species.def();
+        let position = transform.translation.xy();
+        let heading = velocity.linear.try_normalize().unwrap_or(Vec2::X);
+
+        let target = match brain.behavior {
+            Behavior::Hunt(target) | Behavior::Harass(target) => target_query
+                .get(target)
+                .ok()
+                .map(|target| target.translation.xy()),
+            Behavior::Wander => None,
+        };
+        let mut desired = match target {
+            Some(target) => (target - position).normalize_or_zero(),
+            None => Vec2::from_angle(rand_gen.gen_range(-FISH_WANDER_JITTER..FISH_WANDER_JITTER))
+                .rotate(heading),
+        };
+
+        // turn back before reaching the walls
+        if position.x < LEFT_BORDER + FISH_WALL_MARGIN {
+            desired.x = 1.;
+        } else if position.x > RIGHT_BORDER - FISH_WALL_MARGIN {
+            desired.x = -1.;
+        }
+        if position.y < BOTTOM_BORDER + FISH_WALL_MARGIN {
+            desired.y = 1.;
+        } else if position.y > TOP_BORDER - FISH_WALL_MARGIN {
+            desired.y = -1.;
+        }
+
+        // an inflated pufferfish can barely swim
+        let speed = match inflatable {
+            Some(inflatable) if inflatable.inflated => def.max_speed * 0.2,
+            _ => def.max_speed,
+        };
+        acceleration.linear =
+            (desired.normalize_or_zero() * speed - velocity.linear).clamp_length_max(def.max_force);
+    }
+}
+
+pub fn fish_face_velocity(
+    mut fish_query: Query<(&mut Transform, &mut Sprite, &KinematicVelocity), With<Fish>>,
+) {
+    for (mut transform, mut sprite, velocity) in fish_query.iter_mut() {
+        if velocity.linear.length_squared() < 1. {
+            continue;
+        }
+        let angle = velocity.linear.y.atan2(velocity.linear.x);
+        transform.rotation = Quat::from_rotation_z(angle);
+        // stay upright when swimming left
+        sprite.flip_y = angle.abs() > PI / 2.;
+    }
+}
+
+pub fn puffer_inflate(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut puffer_query: Query<(Entity, &Species, &mut Transform, &mut Inflatable), With<Fish>>,
+    player_query: Query<&Transform, (With<Whale>, Without<Fish>)>,
+) {
+    for (entity, species, mut transform, mut inflatable) in puffer_query.iter_mut() {
+        let def = species.def();
+        let position = transform.translation.xy();
+        let threatened = player_query
+            .iter()
+            .any(|player| player.translation.xy().distance(position) < def.sight);
+
+        if threatened {
+            inflatable.deflate.reset();
+            if !inflatable.inflated {
+                inflatable.inflated = true;
+                transform.scale = Vec3::new(PUFFER_INFLATED_SCALE, PUFFER_INFLATED_SCALE, 1.);
+                commands.entity(entity).insert(Hazard {
+                    damage: def.contact_damage,
+                });
+            }
+        } else if inflatable.inflated && inflatable.deflate.tick(time.delta()).finished() {
+            inflatable.inflated = false;
+            transform.scale = Vec3::ONE;
+            commands.entity(entity).remove::<Hazard>();
+        }
+    }
+}
+
+pub fn fish_eat_krill(
+    mut commands: Commands,
+    rapier_context: Res<RapierContext>,
+    fish_query: Query<(Entity, &Species), With<Fish>>,
+    krill_query: Query<&Transform, With<Krill>>,
+    mut eaten_krill: ResMut<EatenKrill>,
+    mut krill_eaten_events: EventWriter<KrillEatenEvent>,
+) {
+    for (fish, species) in fish_query.iter() {
+        if species.def().diet != Diet::Krill {
+            continue;
+        }
+        for (a, b, intersecting) in rapier_context.intersections_with(fish) {
+            let krill = if a == fish { b } else { a };
+            let Ok(krill_transform) = krill_query.get(krill) else {
+                continue;
+            };
+            if intersecting && eaten_krill.claim(krill) {
+                krill_eaten_events.send(KrillEatenEvent {
+                    eater: fish,
+                    position: krill_transform.translation.xy(),
+                });
+                commands.entity(krill).despawn();
+            }
+        }
+    }
+}
+
+/// A fish was killed, by `killer` if anyone is to blame.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct FishKilledEvent {
+    pub killer: Option<Entity>,
+    pub position: Vec2,
+}
+
+pub fn fish_die(
+    mut commands: Commands,
+    mut death_events: EventReader<DeathEvent>,
+    fish_query: Query<&Transform, With<Fish>>,
+    mut fish_killed_events: EventWriter<FishKilledEvent>,
+) {
+    for event in death_events.read() {
+        if let Ok(transform) = fish_query.get(event.entity) {
+            fish_killed_events.send(FishKilledEvent {
+                killer: event.killer,
+                position: transform.translation.xy(),
+            });
+            commands.entity(event.entity).despawn_recursive();
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use bevy::prelude::*;
+
+use crate::{in_gameplay, player::KrillEatenEvent};
+
+// mass gained per krill swallowed
+const KRILL_MASS: f32 = 1.0;
+
+pub struct GrowthPlugin;
+
+impl Plugin for GrowthPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Growth>()
+            .register_type::<GrowthCurves>()
+            .init_resource::<GrowthCurves>()
+            .add_systems(
+                Update,
+                (gain_mass, apply_growth).chain().run_if(in_gameplay),
+            );
+    }
+}
+
+/// Piecewise linear curve through `(mass, value)` points sorted by mass, held flat past either end.
+#[derive(Debug, Clone, PartialEq, Default, Reflect)]
+pub struct GrowthCurve {
+    pub points: Vec<Vec2>,
+}
+
+impl GrowthCurve {
+    pub fn new(points: &[(f32, f32)]) -> Self {
+        Self {
+            points: points.iter().map(|&(x, y)| Vec2::new(x, y)).collect(),
+        }
+    }
+
+    pub fn sample(&self, mass: f32) -> f32 {
+        let Some(first) = self.points.first() else {
+            return 1.0;
+        };
+        if mass <= first.x {
+            return first.y;
+        }
+        for pair in self.points.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            if mass <= b.x {
+                let t = (mass - a.x) / (b.x - a.x).max(f32::EPSILON);
+                return a.y + (b.y - a.y) * t;
+            }
+        }
+        self.points[self.points.len() - 1].y
+    }
+}
+
+/// Multipliers applied to a whale as it gains mass, tuned by designers.
+#[derive(Resource, Debug, Clone, Reflect)]
+#[reflect(Resource)]
+pub struct GrowthCurves {
+    /// sprite, collider and mouth sensor size
+    pub size: GrowthCurve,
+    pub speed: GrowthCurve,
+    pub turn_rate: GrowthCurve,
+    /// how far away krill start fleeing
+    pub fear_radius: GrowthCurve,
+}
+
+impl Default for GrowthCurves {
+    fn default() -> Self {
+        Self {
+            size: GrowthCurve::new(&[(0., 1.), (50., 1.5), (200., 2.2), (500., 3.)]),
+            speed: GrowthCurve::new(&[(0., 1.), (50., 1.15), (200., 1.3), (500., 1.4)]),
+            turn_rate: GrowthCurve::new(&[(0., 1.), (200., 0.7), (500., 0.5)]),
+            fear_radius: GrowthCurve::new(&[(0., 1.), (200., 1.6), (500., 2.2)]),
+        }
+    }
+}
+
+/// Mass eaten so far and the multipliers it currently grants.
+#[derive(Clone, Copy, PartialEq, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct Growth {
+    pub mass: f32,
+    /// the unscaled size of the entity, that `size` multiplies
+    pub base_scale: f32,
+    pub size: f32,
+    pub speed: f32,
+    pub turn_rate: f32,
+    pub fear_radius: f32,
+}
+
+impl Default for Growth {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+impl Growth {
+    pub fn new(base_scale: f32) -> Self {
+        Self {
+            mass: 0.0,
+            base_scale,
+            size: 1.0,
+            speed: 1.0,
+            turn_rate: 1.0,
+            fear_radius: 1.0,
+        }
+    }
+}
+
+fn gain_mass(mut krill_eaten_events: EventReader<KrillEatenEvent>, mut query: Query<&mut Growth>) {
+    for event in krill_eaten_events.read() {
+        if let Ok(mut growth) = query.get_mut(event.eater) {
+            growth.mass += KRILL_MASS;
+        }
+    }
+}
+
+// Rapier scales colliders with the transform, so the body and mouth sensor grow with the sprite.
+fn apply_growth(
+    curves: Res<GrowthCurves>,
+    mut query: Query<(&mut Growth, &mut Transform), Changed<Growth>>,
+) {
+    for (mut growth, mut transform) in &mut query {
+        let mass = growth.mass;
+        growth.size = curves.size.sample(mass);
+        growth.speed = curves.speed.sample(mass);
+        growth.turn_rate = curves.turn_rate.sample(mass);
+        growth.fear_radius = curves.fear_radius.sample(mass);
+
+        let scale = growth.base_scale * growth.size;
+        transform.scale = Vec3::new(scale, scale, 1.0);
+    }
+}
@@ -0,0 +1,198 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::RapierContext;
+
+use crate::in_gameplay;
+
+const FLASH_INTERVAL: f32 = 0.1;
+const DEFAULT_HEALTH: f32 = 100.0;
+
+pub struct HealthPlugin;
+
+impl Plugin for HealthPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Health>()
+            .register_type::<Hazard>()
+            .register_type::<GodMode>()
+            .add_event::<DamageEvent>()
+            .add_event::<DamageTakenEvent>()
+            .add_event::<DeathEvent>()
+            .add_systems(
+                Update,
+                (hazard_contact_damage, apply_damage, tick_invulnerability)
+                    .chain()
+                    .run_if(in_gameplay),
+            );
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct Health {
+    pub current: f32,
+    pub max: f32,
+    /// seconds of invulnerability granted after each hit
+    pub invulnerability: f32,
+}
+
+impl Default for Health {
+    fn default() -> Self {
+        Self::new(DEFAULT_HEALTH, 0.0)
+    }
+}
+
+impl Health {
+    pub fn new(max: f32, invulnerability: f32) -> Self {
+        Self {
+            current: max,
+            max,
+            invulnerability,
+        }
+    }
+
+    pub fn fraction(&self) -> f32 {
+        self.current / self.max
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.current <= 0.0
+    }
+}
+
+/// Hurts any entity with `Health` that it touches.
+#[derive(Clone, Copy, PartialEq, Debug, Default, Component, Reflect)]
+#[reflect(Component)]
+pub struct Hazard {
+    pub damage: f32,
+}
+
+/// While present, damage is ignored and the sprite flashes.
+#[derive(Component, Debug)]
+pub struct Invulnerable {
+    pub timer: Timer,
+    flash: Timer,
+}
+
+impl Invulnerable {
+    pub fn from_seconds(seconds: f32) -> Self {
+        Self {
+            timer: Timer::from_seconds(seconds, TimerMode::Once),
+            flash: Timer::from_seconds(FLASH_INTERVAL, TimerMode::Repeating),
+        }
+    }
+}
+
+/// Ignores all damage, without the flashing of `Invulnerable`. Toggled from the dev console.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Component, Reflect)]
+#[reflect(Component)]
+pub struct GodMode;
+
+/// Asks for `amount` of damage to be dealt to `target`. Predators, hazards and attacks send these.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct DamageEvent {
+    pub target: Entity,
+    pub amount: f32,
+    /// who dealt it, credited with the kill if it is fatal
+    pub source: Option<Entity>,
+}
+
+/// Damage that actually landed, for audio, UI and camera shake to react to.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct DamageTakenEvent {
+    pub target: Entity,
+    pub amount: f32,
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct DeathEvent {
+    pub entity: Entity,
+    pub killer: Option<Entity>,
+}
+
+fn hazard_contact_damage(
+    rapier_context: Res<RapierContext>,
+    health_query: Query<Entity, (With<Health>, Without<Invulnerable>)>,
+    hazard_query: Query<&Hazard>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    for entity in &health_query {
+        let touching = rapier_context
+            .contacts_with(entity)
+            .filter(|contact| contact.has_any_active_contacts())
+            .map(|contact| {
+                if contact.collider1() == entity {
+                    contact.collider2()
+                } else {
+                    contact.collider1()
+                }
+            })
+            .chain(
+                rapier_context
+                    .intersections_with(entity)
+                    .filter(|(_, _, intersecting)| *intersecting)
+                    .map(|(a, b, _)| if a == entity { b } else { a }),
+            );
+
+        for other in touching {
+            if let Ok(hazard) = hazard_query.get(other) {
+                damage_events.send(DamageEvent {
+                    target: entity,
+                    amount: hazard.damage,
+                    source: Some(other),
+                });
+            }
+        }
+    }
+}
+
+fn apply_damage(
+    mut commands: Commands,
+    mut damage_events: EventReader<DamageEvent>,
+    mut health_query: Query<(&mut Health, Has<Invulnerable>, Has<GodMode>)>,
+    mut damage_taken_events: EventWriter<DamageTakenEvent>,
+    mut death_events: EventWriter<DeathEvent>,
+) {
+    for event in damage_events.read() {
+        let Ok((mut health, invulnerable, god_mode)) = health_query.get_mut(event.target) else {
+            continue;
+        };
+        // several hits can arrive in one frame, only the first lands
+        if invulnerable || god_mode || health.is_dead() {
+            continue;
+        }
+
+        health.current = (health.current - event.amount).max(0.0);
+        damage_taken_events.send(DamageTakenEvent {
+            target: event.target,
+            amount: event.amount,
+        });
+
+        if health.is_dead() {
+            death_events.send(DeathEvent {
+                entity: event.target,
+                killer: event.source,
+            });
+        } else if health.invulnerability > 0.0 {
+            commands
+                .entity(event.target)
+                .insert(Invulnerable::from_seconds(health.invulnerability));
+        }
+    }
+}
+
+fn tick_invulnerability(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Invulnerable, &mut Visibility)>,
+) {
+    for (entity, mut invulnerable, mut visibility) in &mut query {
+        if invulnerable.timer.tick(time.delta()).finished() {
+            *visibility = Visibility::Inherited;
+            commands.entity(entity).remove::<Invulnerable>();
+        } else if invulnerable.flash.tick(time.delta()).just_finished() {
+            *visibility = match *visibility {
+                Visibility::Hidden => Visibility::Inherited,
+                _ => Visibility::Hidden,
+            };
+        }
+    }
+}
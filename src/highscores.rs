@@ -0,0 +1,375 @@
+use std::cmp::Reverse;
+
+use bevy::{prelude::*, window::ReceivedCharacter};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    despawn::StateScoped, display::systems::DisplayData, krill::systems::RunSeed,
+    menu::navigate_menus, scoring::GameMode, storage, GameState,
+};
+
+const TABLE_SIZE: usize = 10;
+const NAME_MAX_LEN: usize = 12;
+const DEFAULT_NAME: &str = "Whale";
+const SAVE_NAME: &str = "highscores";
+
+pub struct HighScoresPlugin;
+
+impl Plugin for HighScoresPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(HighScores::load())
+            .init_resource::<NameEntry>()
+            .add_systems(OnEnter(GameState::GameOver), start_name_entry)
+            .add_systems(OnEnter(GameState::Victory), start_name_entry)
+            .add_systems(OnEnter(GameState::Leaderboard), show_leaderboard)
+            .add_systems(
+                Update,
+                // after the menus, so the press that saves doesn't also leave the end screen
+                enter_name
+                    .after(navigate_menus)
+                    .run_if(in_state(GameState::GameOver).or_else(in_state(GameState::Victory))),
+            );
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct HighScore {
+    pub name: String,
+    pub score: usize,
+    pub mode: GameMode,
+    /// seconds since the unix epoch
+    pub date: u64,
+    pub seed: u64,
+}
+
+/// The best runs, highest first, kept between launches.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct HighScores {
+    pub entries: Vec<HighScore>,
+}
+
+impl HighScores {
+    /// Reads the saved table. A missing or unreadable save just means an empty table.
+    pub fn load() -> Self {
+        let Some(saved) = storage::read(SAVE_NAME) else {
+            return Self::default();
+        };
+        match ron::from_str::<Vec<HighScore>>(&saved) {
+            Ok(mut entries) => {
+                entries.sort_by_key(|entry| Reverse(entry.score));
+                entries.truncate(TABLE_SIZE);
+                Self { entries }
+            }
+            Err(error) => {
+                warn!("ignoring corrupted high scores: {error}");
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        match ron::to_string(&self.entries) {
+            Ok(serialized) => {
+                if let Err(error) = storage::write(SAVE_NAME, &serialized) {
+                    warn!("could not save high scores: {error}");
+                }
+            }
+            Err(error) => warn!("could not serialize high scores: {error}"),
+        }
+    }
+
+    pub fn qualifies(&self, score: usize) -> bool {
+        score > 0
+            && (self.entries.len() < TABLE_SIZE
+                || self.entries.last().is_some_and(|last| score > last.score))
+    }
+
+    pub fn insert(&mut self, entry: HighScore) {
+        // ties go below the scores already on the table
+        let index = self
+            .entries
+            .partition_point(|other| other.score >= entry.score);
+        self.entries.insert(index, entry);
+        self.entries.truncate(TABLE_SIZE);
+    }
+}
+
+/// The name being typed in for a new high score, while one is pending.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct NameEntry {
+    pub pending: bool,
+    pub name: String,
+}
+
+#[derive(Component, Debug)]
+pub struct NameText;
+
+/// `YYYY-MM-DD` for a unix timestamp, in UTC.
+pub fn format_date(timestamp: u64) -> String {
+    // Howard Hinnant's days-to-civil algorithm
+    let days = (timestamp / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+fn start_name_entry(
+    mut commands: Commands,
+    state: Res<State<GameState>>,
+    high_scores: Res<HighScores>,
+    display_data: Res<DisplayData>,
+    mut name_entry: ResMut<NameEntry>,
+) {
+    name_entry.name.clear();
+    name_entry.pending = high_scores.qualifies(display_data.total_score);
+    if !name_entry.pending {
+        return;
+    }
+
+    // sits under the end screen's title
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    bottom: Val::Percent(20.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            StateScoped(state.get().clone()),
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                format!(
+                    "New high score: {}! Type your name, Enter to save (A or Start on a gamepad)",
+                    display_data.total_score
+                ),
+                TextStyle {
+                    font_size: 25.0,
+                    color: Color::GOLD,
+                    ..default()
+                },
+            ));
+            parent.spawn((
+                TextBundle::from_section(
+                    "_",
+                    TextStyle {
+                        font_size: 40.0,
+                        ..default()
+                    },
+                ),
+                NameText,
+            ));
+        });
+}
+
+fn enter_name(
+    mut characters: EventReader<ReceivedCharacter>,
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepad_input: Res<Input<GamepadButton>>,
+    gamepads: Res<Gamepads>,
+    mut name_entry: ResMut<NameEntry>,
+    mut high_scores: ResMut<HighScores>,
+    display_data: Res<DisplayData>,
+    mode: Res<GameMode>,
+    seed: Option<Res<RunSeed>>,
+    mut text_query: Query<&mut Text, With<NameText>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if !name_entry.pending {
+        characters.clear();
+        return;
+    }
+
+    for event in characters.read() {
+        if (event.char.is_alphanumeric() || event.char == ' ')
+            && name_entry.name.chars().count() < NAME_MAX_LEN
+        {
+            name_entry.name.push(event.char);
+        }
+    }
+    if keyboard_input.just_pressed(KeyCode::Back) {
+        name_entry.name.pop();
+    }
+
+    // a gamepad can't type, so it saves under the default name
+    let gamepad_confirm = gamepads.iter().any(|gamepad| {
+        [GamepadButtonType::South, GamepadButtonType::Start]
+            .into_iter()
+            .any(|button| gamepad_input.just_pressed(GamepadButton::new(gamepad, button)))
+    });
+    if keyboard_input.just_pressed(KeyCode::Return) || gamepad_confirm {
+        let name = name_entry.name.trim();
+        high_scores.insert(HighScore {
+            name: if name.is_empty() {
+                DEFAULT_NAME.to_string()
+            } else {
+                name.to_string()
+            },
+            score: display_data.total_score,
+            mode: *mode,
+            date: storage::now(),
+            seed: seed.map_or(0, |seed| seed.0),
+        });
+        high_scores.save();
+        name_entry.pending = false;
+        next_state.set(GameState::Leaderboard);
+    } else if name_entry.is_changed() {
+        for mut text in &mut text_query {
+            text.sections[0].value = format!("{}_", name_entry.name);
+        }
+    }
+}
+
+fn show_leaderboard(mut commands: Commands, high_scores: Res<HighScores>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(4.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.05, 0.8).into(),
+                ..default()
+            },
+            StateScoped(GameState::Leaderboard),
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "High Scores",
+                TextStyle {
+                    font_size: 50.0,
+                    color: Color::GOLD,
+                    ..default()
+                },
+            ));
+            if high_scores.entries.is_empty() {
+                parent.spawn(TextBundle::from_section(
+                    "No scores yet",
+                    TextStyle {
+                        font_size: 20.0,
+                        ..default()
+                    },
+                ));
+            }
+            for (rank, entry) in high_scores.entries.iter().enumerate() {
+                parent.spawn(TextBundle::from_section(
+                    format!(
+                        "{:>2}. {:<12} {:>7}  {:<7} {}  #{:016x}",
+                        rank + 1,
+                        entry.name,
+                        entry.score,
+                        entry.mode.name(),
+                        format_date(entry.date),
+                        entry.seed,
+                    ),
+                    TextStyle {
+                        font_size: 20.0,
+                        ..default()
+                    },
+                ));
+            }
+            parent.spawn(TextBundle::from_section(
+                "Enter for the main menu",
+                TextStyle {
+                    font_size: 20.0,
+                    ..default()
+                },
+            ));
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, score: usize) -> HighScore {
+        HighScore {
+            name: name.to_string(),
+            score,
+            mode: GameMode::Classic,
+            date: 0,
+            seed: 0,
+        }
+    }
+
+    fn full_table() -> HighScores {
+        let mut high_scores = HighScores::default();
+        for score in 1..=TABLE_SIZE {
+            high_scores.insert(entry("Whale", score * 10));
+        }
+        high_scores
+    }
+
+    #[test]
+    fn formats_the_epoch() {
+        assert_eq!(format_date(0), "1970-01-01");
+    }
+
+    #[test]
+    fn formats_leap_days() {
+        assert_eq!(format_date(951_782_400), "2000-02-29");
+        assert_eq!(format_date(951_868_800), "2000-03-01");
+        assert_eq!(format_date(1_709_164_800), "2024-02-29");
+        // the last second of the day is still the same date
+        assert_eq!(format_date(1_709_251_199), "2024-02-29");
+    }
+
+    #[test]
+    fn ties_rank_below_existing_entries() {
+        let mut high_scores = HighScores::default();
+        high_scores.insert(entry("First", 100));
+        high_scores.insert(entry("Second", 100));
+        high_scores.insert(entry("Best", 200));
+        let names: Vec<_> = high_scores
+            .entries
+            .iter()
+            .map(|e| e.name.as_str())
+            .collect();
+        assert_eq!(names, ["Best", "First", "Second"]);
+    }
+
+    #[test]
+    fn truncates_at_table_size() {
+        let mut high_scores = full_table();
+        assert_eq!(high_scores.entries.len(), TABLE_SIZE);
+
+        high_scores.insert(entry("New", 55));
+        assert_eq!(high_scores.entries.len(), TABLE_SIZE);
+        assert!(high_scores.entries.iter().any(|e| e.name == "New"));
+        assert_eq!(high_scores.entries.last().unwrap().score, 20);
+
+        // a tie with the last place would rank below it, off the table
+        high_scores.insert(entry("Tied", 20));
+        assert!(high_scores.entries.iter().all(|e| e.name != "Tied"));
+    }
+
+    #[test]
+    fn qualifies_only_above_a_full_table() {
+        let high_scores = full_table();
+        assert!(!high_scores.qualifies(10));
+        assert!(high_scores.qualifies(11));
+        assert!(HighScores::default().qualifies(1));
+        assert!(!HighScores::default().qualifies(0));
+    }
+}
@@ -0,0 +1,460 @@
+use bevy::{prelude::*, time::Stopwatch, window::PrimaryWindow};
+
+use crate::{
+    camera::{VIEW_MIN_HEIGHT, VIEW_MIN_WIDTH},
+    despawn::RunScoped,
+    health::Health,
+    in_gameplay,
+    krill::systems::SwarmDensity,
+    player::{Gulp, KrillEatenEvent, Player, PlayerId, Score, Stamina, MAX_PLAYERS},
+    scoring::{Combo, ScoringRules},
+    sonar::Sonar,
+    START_RUN,
+};
+
+// the layout is designed for a window this many times the camera's minimum view
+const DESIGN_ZOOM: f32 = 5.0;
+const BAR_WIDTH: f32 = 120.0;
+const BAR_HEIGHT: f32 = 10.0;
+const COMBO_BAR_HEIGHT: f32 = 4.0;
+const ICON_SIZE: f32 = 24.0;
+const PANEL_MARGIN: f32 = 5.0;
+const FONT_SIZE: f32 = 25.0;
+const SMALL_FONT_SIZE: f32 = 18.0;
+
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunStats>()
+            .init_resource::<PlayerHuds>()
+            .add_systems(START_RUN, (reset_hud, spawn_run_panel))
+            .add_systems(
+                Update,
+                (
+                    (
+                        tick_run_clock,
+                        count_krill_eaten,
+                        count_krill_remaining,
+                        collect_player_huds,
+                    )
+                        .run_if(in_gameplay),
+                    spawn_player_panels,
+                    update_run_panel.run_if(resource_changed::<RunStats>()),
+                    (
+                        update_score_text,
+                        update_bars,
+                        update_combo_meter,
+                        update_cooldown_icons,
+                    )
+                        .run_if(resource_changed::<PlayerHuds>()),
+                )
+                    .chain(),
+            )
+            .add_systems(PostUpdate, scale_to_window);
+    }
+}
+
+/// Run-wide figures for the HUD, gathered as the run goes.
+#[derive(Resource, Debug, Default)]
+pub struct RunStats {
+    pub clock: Stopwatch,
+    /// whole seconds on `clock`; the timer text only changes when this does
+    pub seconds: u64,
+    pub krill_eaten: usize,
+    pub krill_remaining: usize,
+}
+
+/// What one player's panel shows. Fractions run from 0 to 1.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct PlayerHud {
+    pub score: usize,
+    pub health: f32,
+    pub stamina: f32,
+    pub combo_chain: usize,
+    pub combo_multiplier: f32,
+    /// how much of the combo window is left before the chain lapses
+    pub combo_left: f32,
+    pub sonar_ready: f32,
+    pub gulp_ready: f32,
+}
+
+/// A snapshot of every player for the HUD, indexed by `PlayerId`. Only changes when something on
+/// it does, so the panels are only touched then.
+#[derive(Resource, PartialEq, Debug, Default)]
+pub struct PlayerHuds(pub [Option<PlayerHud>; MAX_PLAYERS]);
+
+#[derive(Component, Debug)]
+pub struct TimerText;
+
+#[derive(Component, Debug)]
+pub struct KrillText;
+
+/// Shows the score of player `id`.
+#[derive(Component, Debug)]
+pub struct ScoreText {
+    pub id: usize,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum BarKind {
+    Health,
+    Stamina,
+    Combo,
+}
+
+/// The filled part of one of player `id`'s bars.
+#[derive(Component, Debug)]
+pub struct HudBar {
+    pub id: usize,
+    pub kind: BarKind,
+}
+
+#[derive(Component, Debug)]
+pub struct ComboText {
+    pub id: usize,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Ability {
+    Sonar,
+    Gulp,
+}
+
+/// The shade over an ability icon, shrinking as the ability comes off cooldown.
+#[derive(Component, Debug)]
+pub struct CooldownShade {
+    pub id: usize,
+    pub ability: Ability,
+}
+
+fn format_clock(seconds: u64) -> String {
+    format!("{:02}:{:02}", seconds / 60, seconds % 60)
+}
+
+fn reset_hud(mut run_stats: ResMut<RunStats>, mut player_huds: ResMut<PlayerHuds>) {
+    *run_stats = RunStats::default();
+    *player_huds = PlayerHuds::default();
+}
+
+fn tick_run_clock(time: Res<Time>, mut run_stats: ResMut<RunStats>) {
+    // the stopwatch ticks every frame, but only a new second is worth redrawing for
+    let stats = run_stats.bypass_change_detection();
+    stats.clock.tick(time.delta());
+    let seconds = stats.clock.elapsed().as_secs();
+    if seconds != stats.seconds {
+        run_stats.seconds = seconds;
+    }
+}
+
+fn count_krill_eaten(
+    mut krill_eaten_events: EventReader<KrillEatenEvent>,
+    player_query: Query<(), With<Player>>,
+    mut run_stats: ResMut<RunStats>,
+) {
+    let eaten = krill_eaten_events
+        .read()
+        .filter(|event| player_query.contains(event.eater))
+        .count();
+    if eaten > 0 {
+        run_stats.krill_eaten += eaten;
+    }
+}
+
+// The swarm density grid already counts the krill a few times a second.
+fn count_krill_remaining(density: Res<SwarmDensity>, mut run_stats: ResMut<RunStats>) {
+    let remaining = density.counts.iter().sum::<u32>() as usize;
+    if remaining != run_stats.krill_remaining {
+        run_stats.krill_remaining = remaining;
+    }
+}
+
+fn collect_player_huds(
+    rules: Res<ScoringRules>,
+    player_query: Query<(
+        &PlayerId,
+        &Score,
+        &Health,
+        &Stamina,
+        Option<&Combo>,
+        &Sonar,
+        &Gulp,
+    )>,
+    mut player_huds: ResMut<PlayerHuds>,
+) {
+    let mut huds = PlayerHuds::default();
+    for (id, score, health, stamina, combo, sonar, gulp) in &player_query {
+        let (combo_chain, combo_multiplier, combo_left) = combo.map_or((0, 1.0, 0.0), |combo| {
+            let left = if combo.chain == 0 {
+                0.0
+            } else {
+                1.0 - combo.timer.percent()
+            };
+            (combo.chain, combo.multiplier(&rules), left)
+        });
+        huds.0[id.0 % MAX_PLAYERS] = Some(PlayerHud {
+            score: score.0,
+            health: health.fraction(),
+            stamina: stamina.fraction(),
+            combo_chain,
+            combo_multiplier,
+            combo_left,
+            sonar_ready: sonar.cooldown.percent(),
+            gulp_ready: gulp.cooldown.percent(),
+        });
+    }
+    player_huds.set_if_neq(huds);
+}
+
+fn hud_text(value: impl Into<String>, font_size: f32, color: Color) -> TextBundle {
+    TextBundle::from_section(
+        value,
+        TextStyle {
+            font_size,
+            color,
+            ..default()
+        },
+    )
+}
+
+// Timer and krill counts, across the top of the screen.
+fn spawn_run_panel(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    top: Val::Px(PANEL_MARGIN),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            RunScoped,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                hud_text(format_clock(0), FONT_SIZE, Color::WHITE),
+                TimerText,
+            ));
+            parent.spawn((hud_text("", SMALL_FONT_SIZE, Color::PINK), KrillText));
+        });
+}
+
+fn update_run_panel(
+    run_stats: Res<RunStats>,
+    mut timer_query: Query<&mut Text, (With<TimerText>, Without<KrillText>)>,
+    mut krill_query: Query<&mut Text, (With<KrillText>, Without<TimerText>)>,
+) {
+    for mut text in &mut timer_query {
+        text.sections[0].value = format_clock(run_stats.seconds);
+    }
+    for mut text in &mut krill_query {
+        text.sections[0].value = format!(
+            "Krill: {} left, {} eaten",
+            run_stats.krill_remaining, run_stats.krill_eaten
+        );
+    }
+}
+
+// Each player gets a panel in their own corner: 1 bottom left, 2 bottom right, 3 top left,
+// 4 top right.
+fn spawn_player_panels(mut commands: Commands, player_query: Query<&PlayerId, Added<PlayerId>>) {
+    for id in &player_query {
+        let mut style = Style {
+            position_type: PositionType::Absolute,
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(PANEL_MARGIN),
+            ..default()
+        };
+        if id.0 % 2 == 0 {
+            style.left = Val::Px(PANEL_MARGIN);
+        } else {
+            style.right = Val::Px(PANEL_MARGIN);
+            style.align_items = AlignItems::FlexEnd;
+        }
+        if id.0 < 2 {
+            style.bottom = Val::Px(PANEL_MARGIN);
+        } else {
+            style.top = Val::Px(PANEL_MARGIN);
+        }
+
+        let id = id.0;
+        commands
+            .spawn((NodeBundle { style, ..default() }, RunScoped))
+            .with_children(|parent| {
+                parent.spawn((
+                    TextBundle::from_sections([
+                        TextSection::new(
+                            format!("P{} Score: ", id + 1),
+                            TextStyle {
+                                font_size: FONT_SIZE,
+                                color: PlayerId(id).color(),
+                                ..default()
+                            },
+                        ),
+                        TextSection::new(
+                            "0",
+                            TextStyle {
+                                font_size: FONT_SIZE,
+                                ..default()
+                            },
+                        ),
+                    ]),
+                    ScoreText { id },
+                ));
+                spawn_bar(parent, BAR_HEIGHT, Color::RED, id, BarKind::Health);
+                spawn_bar(parent, BAR_HEIGHT, Color::GOLD, id, BarKind::Stamina);
+                parent.spawn((
+                    hud_text("", SMALL_FONT_SIZE, Color::ORANGE),
+                    ComboText { id },
+                ));
+                spawn_bar(parent, COMBO_BAR_HEIGHT, Color::ORANGE, id, BarKind::Combo);
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            column_gap: Val::Px(PANEL_MARGIN),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        spawn_icon(parent, "S", Color::CYAN, id, Ability::Sonar);
+                        spawn_icon(parent, "G", Color::PINK, id, Ability::Gulp);
+                    });
+            });
+    }
+}
+
+fn spawn_bar(parent: &mut ChildBuilder, height: f32, color: Color, id: usize, kind: BarKind) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Px(BAR_WIDTH),
+                height: Val::Px(height),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.5).into(),
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    background_color: color.into(),
+                    ..default()
+                },
+                HudBar { id, kind },
+            ));
+        });
+}
+
+fn spawn_icon(parent: &mut ChildBuilder, label: &str, color: Color, id: usize, ability: Ability) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Px(ICON_SIZE),
+                height: Val::Px(ICON_SIZE),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            background_color: color.into(),
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn(hud_text(label, SMALL_FONT_SIZE, Color::BLACK));
+            // drawn over the label, shrinking down from the top
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(0.0),
+                        top: Val::Px(0.0),
+                        ..default()
+                    },
+                    background_color: Color::rgba(0.0, 0.0, 0.0, 0.7).into(),
+                    ..default()
+                },
+                CooldownShade { id, ability },
+            ));
+        });
+}
+
+fn update_score_text(player_huds: Res<PlayerHuds>, mut text_query: Query<(&mut Text, &ScoreText)>) {
+    for (mut text, score_text) in &mut text_query {
+        if let Some(hud) = &player_huds.0[score_text.id] {
+            text.sections[1].value = format!("{}", hud.score);
+        }
+    }
+}
+
+fn update_bars(player_huds: Res<PlayerHuds>, mut bar_query: Query<(&mut Style, &HudBar)>) {
+    for (mut style, bar) in &mut bar_query {
+        if let Some(hud) = &player_huds.0[bar.id] {
+            let fraction = match bar.kind {
+                BarKind::Health => hud.health,
+                BarKind::Stamina => hud.stamina,
+                BarKind::Combo => hud.combo_left,
+            };
+            style.width = Val::Percent(fraction * 100.0);
+        }
+    }
+}
+
+fn update_combo_meter(
+    player_huds: Res<PlayerHuds>,
+    mut text_query: Query<(&mut Text, &ComboText)>,
+) {
+    for (mut text, combo_text) in &mut text_query {
+        if let Some(hud) = &player_huds.0[combo_text.id] {
+            let value = if hud.combo_chain == 0 {
+                String::new()
+            } else {
+                format!("Combo {} x{:.1}", hud.combo_chain, hud.combo_multiplier)
+            };
+            if text.sections[0].value != value {
+                text.sections[0].value = value;
+            }
+        }
+    }
+}
+
+fn update_cooldown_icons(
+    player_huds: Res<PlayerHuds>,
+    mut shade_query: Query<(&mut Style, &CooldownShade)>,
+) {
+    for (mut style, shade) in &mut shade_query {
+        if let Some(hud) = &player_huds.0[shade.id] {
+            let ready = match shade.ability {
+                Ability::Sonar => hud.sonar_ready,
+                Ability::Gulp => hud.gulp_ready,
+            };
+            style.height = Val::Percent((1.0 - ready) * 100.0);
+        }
+    }
+}
+
+// Scales the whole UI the same way `ScalingMode::AutoMin` scales the world, so the HUD keeps its
+// size relative to the level whatever the window does.
+fn scale_to_window(
+    window_query: Query<&Window, (With<PrimaryWindow>, Changed<Window>)>,
+    mut ui_scale: ResMut<UiScale>,
+) {
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    let scale =
+        (window.width() / VIEW_MIN_WIDTH).min(window.height() / VIEW_MIN_HEIGHT) / DESIGN_ZOOM;
+    let scale = f64::from(scale.max(f32::EPSILON));
+    if (ui_scale.0 - scale).abs() > f64::EPSILON {
+        ui_scale.0 = scale;
+    }
+}
@@ -1,10 +1,14 @@
 use bevy::prelude::*;
 
-use crate::GameState;
+use crate::{in_gameplay, START_RUN};
 
 use self::systems::*;
 
+#[cfg(feature = "debug")]
+pub mod overlay;
 pub mod systems;
+#[cfg(feature = "debug")]
+pub mod tuning;
 
 pub struct KrillPlugin;
 
@@ -14,25 +18,38 @@ impl Plugin for KrillPlugin {
             .register_type::<SeperationCoe>()
             .register_type::<CohesionCoe>()
             .register_type::<Acceleration>()
+            .register_type::<SwarmTuning>()
+            .init_resource::<SwarmTuning>()
             .init_resource::<AlignCoe>()
             .init_resource::<SeperationCoe>()
             .init_resource::<CohesionCoe>()
+            .init_resource::<SwarmDensity>()
+            .init_resource::<EatenKrill>()
+            .add_event::<SwarmClearedEvent>()
             .add_state::<KrillState>()
-            .add_systems(OnEnter(GameState::Active), spawn_krill)
+            .add_systems(START_RUN, (reset_swarm_density, spawn_krill))
+            .add_systems(First, forget_eaten_krill)
             .add_systems(
                 Update,
                 (
-                    debug_krill,
+                    update_swarm_density,
                     krill_idle_movement.run_if(in_state(KrillState::Idle)),
                     ((
                         ((boid_align, boid_seperation, boid_cohesion), boid_flock).chain(),
-                        (krill_avoid_player, krill_avoid_obstical).chain(),
+                        (
+                            krill_avoid_player,
+                            krill_avoid_sonar,
+                            krill_avoid_obstical,
+                            krill_gulp_suction,
+                        )
+                            .chain(),
                         (krill_update_velocity, krill_rotate_to_face_vel_vec).chain(),
                     )
                         .chain())
                     .run_if(in_state(KrillState::Moving)),
                     // krill_death.run_if(in_state(KrillState::Dead)),
-                ),
+                )
+                    .run_if(in_gameplay),
             );
     }
 }
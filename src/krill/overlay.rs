@@ -0,0 +1,261 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+use bevy_rapier2d::dynamics::Velocity;
+
+use super::systems::{
+    Acceleration, Align, AlignCoe, Cohesion, CohesionCoe, Krill, Seperation, SeperationCoe,
+    SwarmTuning, KRILL_RADIUS,
+};
+use crate::{
+    camera::MainCamera,
+    map::{Obstacal, BOTTOM_BORDER, LEFT_BORDER, RIGHT_BORDER, TOP_BORDER},
+    DebugEvent,
+};
+
+// steering vectors are accelerations, far longer than a krill; this brings them down to size
+const VECTOR_SCALE: f32 = 0.1;
+// how close a click has to land to a krill to select it
+const SELECT_RADIUS: f32 = KRILL_RADIUS * 3.0;
+const VELOCITY_COLOR: Color = Color::WHITE;
+const ALIGN_COLOR: Color = Color::GREEN;
+const SEPERATION_COLOR: Color = Color::RED;
+const COHESION_COLOR: Color = Color::BLUE;
+const PERCEPTION_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.15);
+const NEIGHBOR_COLOR: Color = Color::YELLOW;
+const FEELER_COLOR: Color = Color::ORANGE;
+const SELECTED_COLOR: Color = Color::FUCHSIA;
+
+/// Gizmo overlays for the flocking simulation. F1 to F4 toggle them, and clicking a krill picks
+/// it out for a closer look.
+pub struct FlockingOverlayPlugin;
+
+impl Plugin for FlockingOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FlockingOverlay>().add_systems(
+            Update,
+            (
+                toggle_overlays,
+                select_krill,
+                log_selected_krill,
+                (draw_perception, draw_steering, draw_neighbors, draw_feelers),
+            )
+                .chain(),
+        );
+    }
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct FlockingOverlay {
+    pub perception: bool,
+    pub steering: bool,
+    pub neighbors: bool,
+    pub feelers: bool,
+    pub selected: Option<Entity>,
+}
+
+impl FlockingOverlay {
+    /// Whether per-krill overlays should draw `krill`: just the selected one, or every krill
+    /// when none is selected.
+    fn shows(&self, krill: Entity) -> bool {
+        self.selected.is_none_or(|selected| selected == krill)
+    }
+}
+
+fn toggle_overlays(keyboard_input: Res<Input<KeyCode>>, mut overlay: ResMut<FlockingOverlay>) {
+    let overlay = &mut *overlay;
+    for (key, enabled) in [
+        (KeyCode::F1, &mut overlay.perception),
+        (KeyCode::F2, &mut overlay.steering),
+        (KeyCode::F3, &mut overlay.neighbors),
+        (KeyCode::F4, &mut overlay.feelers),
+    ] {
+        if keyboard_input.just_pressed(key) {
+            *enabled = !*enabled;
+        }
+    }
+}
+
+// Clicking empty water clears the selection.
+fn select_krill(
+    mouse_input: Res<Input<MouseButton>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    krill_query: Query<(Entity, &Transform), With<Krill>>,
+    mut overlay: ResMut<FlockingOverlay>,
+) {
+    if !mouse_input.just_pressed(MouseButton::Left) {
+        // the selected krill may have been eaten
+        if overlay
+            .selected
+            .is_some_and(|selected| !krill_query.contains(selected))
+        {
+            overlay.selected = None;
+        }
+        return;
+    }
+    let Some(cursor) = window_query
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+        .zip(camera_query.get_single().ok())
+        .and_then(|(cursor, (camera, camera_transform))| {
+            camera.viewport_to_world_2d(camera_transform, cursor)
+        })
+    else {
+        return;
+    };
+
+    overlay.selected = krill_query
+        .iter()
+        .map(|(entity, transform)| (entity, transform.translation.xy().distance(cursor)))
+        .filter(|(_, distance)| *distance < SELECT_RADIUS)
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(entity, _)| entity);
+}
+
+fn log_selected_krill(
+    mut debug_event_reader: EventReader<DebugEvent>,
+    overlay: Res<FlockingOverlay>,
+    krill_query: Query<(&Transform, &Velocity, &Acceleration), With<Krill>>,
+) {
+    for _event in debug_event_reader.read() {
+        let Some(selected) = overlay.selected else {
+            info!("no krill selected, click one to inspect it");
+            continue;
+        };
+        if let Ok((transform, velocity, acceleration)) = krill_query.get(selected) {
+            info!("{selected:?} {transform:?} {velocity:?} {acceleration:?}");
+        }
+    }
+}
+
+fn draw_perception(
+    mut gizmos: Gizmos,
+    overlay: Res<FlockingOverlay>,
+    tuning: Res<SwarmTuning>,
+    krill_query: Query<(Entity, &Transform), With<Krill>>,
+) {
+    for (krill, transform) in &krill_query {
+        let position = transform.translation.xy();
+        if overlay.selected == Some(krill) {
+            gizmos.circle_2d(position, KRILL_RADIUS * 1.5, SELECTED_COLOR);
+        }
+        if !overlay.perception {
+            continue;
+        }
+        gizmos.circle_2d(position, tuning.perception_radius, PERCEPTION_COLOR);
+        // the selected krill also shows the range each rule actually looks over
+        if overlay.selected == Some(krill) {
+            gizmos.circle_2d(
+                position,
+                tuning.perception_radius / 1.5,
+                SEPERATION_COLOR.with_a(0.4),
+            );
+            gizmos.circle_2d(
+                position,
+                tuning.perception_radius * 1.1,
+                COHESION_COLOR.with_a(0.4),
+            );
+        }
+    }
+}
+
+fn draw_steering(
+    mut gizmos: Gizmos,
+    overlay: Res<FlockingOverlay>,
+    align_coe: Res<AlignCoe>,
+    seperation_coe: Res<SeperationCoe>,
+    cohesion_coe: Res<CohesionCoe>,
+    krill_query: Query<
+        (
+            Entity,
+            &Transform,
+            &Velocity,
+            &Align,
+            &Seperation,
+            &Cohesion,
+        ),
+        With<Krill>,
+    >,
+) {
+    if !overlay.steering {
+        return;
+    }
+    for (krill, transform, velocity, align, seperation, cohesion) in &krill_query {
+        if !overlay.shows(krill) {
+            continue;
+        }
+        let position = transform.translation.xy();
+        gizmos.line_2d(
+            position,
+            position + velocity.linvel * VECTOR_SCALE,
+            VELOCITY_COLOR,
+        );
+        // drawn as they are weighed into the acceleration
+        for (steering, coe, color) in [
+            (align.vec, align_coe.mag, ALIGN_COLOR),
+            (seperation.vec, seperation_coe.mag, SEPERATION_COLOR),
+            (cohesion.vec, cohesion_coe.mag, COHESION_COLOR),
+        ] {
+            gizmos.line_2d(position, position + steering * coe * VECTOR_SCALE, color);
+        }
+    }
+}
+
+fn draw_neighbors(
+    mut gizmos: Gizmos,
+    overlay: Res<FlockingOverlay>,
+    tuning: Res<SwarmTuning>,
+    krill_query: Query<&Transform, With<Krill>>,
+) {
+    if !overlay.neighbors {
+        return;
+    }
+    let Some(selected) = overlay
+        .selected
+        .and_then(|selected| krill_query.get(selected).ok())
+        .map(|transform| transform.translation.xy())
+    else {
+        return;
+    };
+    for transform in &krill_query {
+        let other = transform.translation.xy();
+        let distance = selected.distance(other);
+        if distance > 0.0 && distance < tuning.perception_radius {
+            gizmos.line_2d(selected, other, NEIGHBOR_COLOR);
+        }
+    }
+}
+
+// The same walls and range `krill_avoid_obstical` steers away from.
+fn draw_feelers(
+    mut gizmos: Gizmos,
+    overlay: Res<FlockingOverlay>,
+    tuning: Res<SwarmTuning>,
+    obstacal_query: Query<&Obstacal>,
+    krill_query: Query<(Entity, &Transform), With<Krill>>,
+) {
+    if !overlay.feelers {
+        return;
+    }
+    let range = tuning.perception_radius * 10.;
+    for (krill, transform) in &krill_query {
+        if !overlay.shows(krill) {
+            continue;
+        }
+        let position = transform.translation.xy();
+        for obstacal in &obstacal_query {
+            let wall = match obstacal {
+                Obstacal::Floor => Vec2::new(position.x, BOTTOM_BORDER),
+                Obstacal::Ceiling => Vec2::new(position.x, TOP_BORDER),
+                Obstacal::LeftWall => Vec2::new(LEFT_BORDER, position.y),
+                Obstacal::RightWall => Vec2::new(RIGHT_BORDER, position.y),
+            };
+            let distance = position.distance(wall);
+            if distance < range {
+                // fainter the farther the wall, as the push falls off
+                let strength = 1.0 - distance / range;
+                gizmos.line_2d(position, wall, FEELER_COLOR.with_a(strength));
+            }
+        }
+    }
+}
@@ -1,18 +1,22 @@
 use std::ops::Range;
 
-use bevy::prelude::*;
+use bevy::{prelude::*, utils::HashSet};
 use bevy_rapier2d::{
     dynamics::{CoefficientCombineRule, RigidBody, Velocity},
     geometry::{Collider, CollisionGroups, Friction, Group, Restitution},
 };
 
 use rand::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     assets::{AnimationIndices, AnimationTimer, ImageAssets},
-    map::{Obstacal, BOTTOM_BORDER, LEFT_BORDER, RIGHT_BORDER, TOP_BORDER},
-    player::Player,
-    DebugEvent,
+    despawn::RunScoped,
+    growth::Growth,
+    map::{LevelBounds, Obstacal, BOTTOM_BORDER, LEFT_BORDER, RIGHT_BORDER, TOP_BORDER},
+    player::{Boost, Dying, Facing, Gulp, KrillEatenEvent, Mouth, Whale},
+    settings::Settings,
+    sonar::{Concealable, SonarPulse},
 };
 
 const ERROR_FROM_ZERO: f32 = 0.05;
@@ -27,11 +31,19 @@ pub const BOID_CHOESION_MAG: f32 = 1.2;
 pub const BOID_PERCEPTION_RADIUS: f32 = 7.;
 
 const KRILL: &str = "Krill";
-const KRILL_ENTITYS_STARTING_AMT: u16 = 600;
+pub const KRILL_ENTITYS_STARTING_AMT: u16 = 600;
 pub const KRILL_RADIUS: f32 = 2.5;
 pub const KRILL_MAX_SPEED: f32 = 50.;
 pub const KRILL_COLLISION_GROUP: Group = Group::GROUP_1;
 const KRILL_AVOIDANCE_MAG: f32 = 50.;
+// a boosting whale is louder, so krill notice it from farther away and flee harder
+const KRILL_BOOST_PANIC_RADIUS: f32 = 1.6;
+const KRILL_BOOST_PANIC_MAG: f32 = 2.;
+const KRILL_SONAR_STARTLE_MAG: f32 = 300.;
+const SWARM_CELL_SIZE: f32 = 20.;
+const SWARM_DENSITY_INTERVAL: f32 = 0.25;
+// a cell must have held at least this many krill for emptying it to count as clearing a swarm
+const SWARM_CLEARED_MIN: u32 = 12;
 const KRILL_RIGID_BODY: RigidBody = RigidBody::Dynamic;
 const KRILL_RESTITUTION_COE: f32 = 1.;
 const KRILL_FRICTION_COE: f32 = 0.;
@@ -52,7 +64,14 @@ pub struct Boid;
 #[derive(Clone, PartialEq, Debug, Default, Component, Reflect)]
 #[reflect(Component)]
 pub struct Acceleration {
-    vec: Vec2,
+    pub vec: Vec2,
+}
+
+impl Acceleration {
+    /// Adds an outside force, such as a whale's suction, for this frame.
+    pub fn apply(&mut self, force: Vec2) {
+        self.vec += force;
+    }
 }
 
 // #[derive(Clone, PartialEq, Debug, Default, Component)]
@@ -62,17 +81,43 @@ pub struct Acceleration {
 
 #[derive(Clone, PartialEq, Debug, Default, Component)]
 pub struct Align {
-    vec: Vec2,
+    pub vec: Vec2,
 }
 
 #[derive(Clone, PartialEq, Debug, Default, Component)]
 pub struct Seperation {
-    vec: Vec2,
+    pub vec: Vec2,
 }
 
 #[derive(Clone, PartialEq, Debug, Default, Component)]
 pub struct Cohesion {
-    vec: Vec2,
+    pub vec: Vec2,
+}
+
+/// Steering distances and strengths for the swarm, in a resource so they can be tuned in play.
+#[derive(Resource, Clone, PartialEq, Debug, Reflect, Serialize, Deserialize)]
+#[reflect(Resource)]
+pub struct SwarmTuning {
+    pub perception_radius: f32,
+    /// the speed boids steer towards
+    pub max_speed: f32,
+    pub avoidance_mag: f32,
+    pub boost_panic_radius: f32,
+    pub boost_panic_mag: f32,
+    pub sonar_startle_mag: f32,
+}
+
+impl Default for SwarmTuning {
+    fn default() -> Self {
+        Self {
+            perception_radius: BOID_PERCEPTION_RADIUS,
+            max_speed: KRILL_MAX_SPEED,
+            avoidance_mag: KRILL_AVOIDANCE_MAG,
+            boost_panic_radius: KRILL_BOOST_PANIC_RADIUS,
+            boost_panic_mag: KRILL_BOOST_PANIC_MAG,
+            sonar_startle_mag: KRILL_SONAR_STARTLE_MAG,
+        }
+    }
 }
 
 #[derive(Resource, Reflect)]
@@ -117,6 +162,64 @@ impl Default for CohesionCoe {
     }
 }
 
+/// A cell that held a swarm has been eaten empty.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct SwarmClearedEvent {
+    /// the centre of the cleared cell
+    pub position: Vec2,
+}
+
+/// Krill counted into a coarse grid over the level, refreshed a few times a second. Cheaper to
+/// read than the krill themselves for anything that cares where the swarms are.
+#[derive(Resource, Debug, Clone)]
+pub struct SwarmDensity {
+    pub origin: Vec2,
+    pub cell_size: f32,
+    pub columns: usize,
+    pub rows: usize,
+    pub counts: Vec<u32>,
+    /// the counts from the update before, to tell when a swarm has gone
+    pub previous: Vec<u32>,
+    /// krill eaten in each cell since the update before
+    pub eaten: Vec<u32>,
+    pub timer: Timer,
+}
+
+impl Default for SwarmDensity {
+    fn default() -> Self {
+        Self {
+            origin: Vec2::ZERO,
+            cell_size: SWARM_CELL_SIZE,
+            columns: 0,
+            rows: 0,
+            counts: Vec::new(),
+            previous: Vec::new(),
+            eaten: Vec::new(),
+            timer: Timer::from_seconds(SWARM_DENSITY_INTERVAL, TimerMode::Repeating),
+        }
+    }
+}
+
+impl SwarmDensity {
+    /// The index of the cell `position` falls in, if it is inside the grid.
+    pub fn cell_index(&self, position: Vec2) -> Option<usize> {
+        let cell = ((position - self.origin) / self.cell_size).floor();
+        if cell.x < 0. || cell.y < 0. {
+            return None;
+        }
+        let (column, row) = (cell.x as usize, cell.y as usize);
+        (column < self.columns && row < self.rows).then_some(row * self.columns + column)
+    }
+
+    /// Every cell's centre and krill count.
+    pub fn cells(&self) -> impl Iterator<Item = (Vec2, u32)> + '_ {
+        self.counts.iter().enumerate().map(|(index, count)| {
+            let cell = Vec2::new((index % self.columns) as f32, (index / self.columns) as f32);
+            (self.origin + (cell + 0.5) * self.cell_size, *count)
+        })
+    }
+}
+
 #[derive(Bundle)]
 pub struct KrillBundle {
     krill: Krill,
@@ -130,6 +233,8 @@ pub struct KrillBundle {
     friction: Friction,
     collision_group: CollisionGroups,
     boid: BoidBundle,
+    concealable: Concealable,
+    run_scoped: RunScoped,
 }
 #[derive(Clone, Eq, PartialEq, Debug, Default, Component)]
 pub struct Krill;
@@ -142,17 +247,9 @@ pub enum KrillState {
     // Dead,
 }
 
-pub fn spawn_krill(mut commands: Commands, image_assets: Res<ImageAssets>) {
-    let mut rand_gen = thread_rng();
-
-    for _ in 0..KRILL_ENTITYS_STARTING_AMT {
-        let random_x = rand_gen.gen_range(SPAWN_X_RANGE);
-        let random_y = rand_gen.gen_range(SPAWN_Y_RANGE);
-        let random_starting_vel =
-            Vec2::new(rand_gen.gen_range(-1.0..1.0), rand_gen.gen_range(-1.0..1.0)).normalize();
-        let random_starting_speed = rand_gen.gen_range(1.0..KRILL_MAX_SPEED);
-
-        commands.spawn(KrillBundle {
+impl KrillBundle {
+    pub fn new(image_assets: &ImageAssets, position: Vec2, velocity: Vec2) -> Self {
+        Self {
             krill: Krill,
             name: Name::new(KRILL),
             sprite: SpriteSheetBundle {
@@ -162,7 +259,7 @@ pub fn spawn_krill(mut commands: Commands, image_assets: Res<ImageAssets>) {
                     ..Default::default()
                 },
                 texture_atlas: image_assets.krill.clone(),
-                transform: Transform::from_translation(Vec3::new(random_x, random_y, 1.)),
+                transform: Transform::from_translation(position.extend(1.)),
                 ..Default::default()
             },
             animation_indices: AnimationIndices { first: 0, last: 1 },
@@ -184,28 +281,72 @@ pub fn spawn_krill(mut commands: Commands, image_assets: Res<ImageAssets>) {
             boid: BoidBundle {
                 boid: Boid,
                 acceleration: Acceleration { vec: Vec2::ZERO },
-                velocity: Velocity::linear(random_starting_vel * random_starting_speed),
+                velocity: Velocity::linear(velocity),
                 align: Align { vec: Vec2::ZERO },
                 seperation: Seperation { vec: Vec2::ZERO },
                 cohesion: Cohesion { vec: Vec2::ZERO },
             },
-        });
+            concealable: Concealable,
+            run_scoped: RunScoped,
+        }
     }
 }
 
-pub fn debug_krill(
-    mut debug_event_reader: EventReader<DebugEvent>,
-    krill_query: Query<(&Transform, &Velocity, &Acceleration), With<Krill>>,
-) {
-    for _event in debug_event_reader.read() {
-        for (krill_transform, krill_vel, krill_acc) in krill_query.iter() {
-            info!("{:?}", krill_transform);
-            info!("{:?}", krill_vel);
-            info!("{:?}", krill_acc);
-        }
+/// Krill swallowed this frame. They're only despawned at the end of it, so a krill touching two
+/// mouths at once would otherwise be eaten, and scored, twice.
+#[derive(Resource, Debug, Default)]
+pub struct EatenKrill(pub HashSet<Entity>);
+
+impl EatenKrill {
+    /// Claims `krill` for an eater; false if something already ate it this frame.
+    pub fn claim(&mut self, krill: Entity) -> bool {
+        self.0.insert(krill)
     }
 }
 
+pub fn forget_eaten_krill(mut eaten: ResMut<EatenKrill>) {
+    eaten.0.clear();
+}
+
+/// The seed the run's krill were scattered with, so a layout can be told apart from another.
+#[derive(Resource, Clone, Copy, Eq, PartialEq, Debug, Default)]
+pub struct RunSeed(pub u64);
+
+pub fn spawn_krill(
+    mut commands: Commands,
+    image_assets: Res<ImageAssets>,
+    settings: Res<Settings>,
+) {
+    let seed = thread_rng().gen();
+    commands.insert_resource(RunSeed(seed));
+    commands.spawn_batch(scatter_krill(
+        &image_assets,
+        settings.krill_quality.krill_count(),
+        seed,
+    ));
+}
+
+/// `count` krill spread over the level, laid out the same way every time for a given `seed`.
+pub fn scatter_krill(image_assets: &ImageAssets, count: usize, seed: u64) -> Vec<KrillBundle> {
+    let mut rand_gen = StdRng::seed_from_u64(seed);
+
+    (0..count)
+        .map(|_| {
+            let random_x = rand_gen.gen_range(SPAWN_X_RANGE);
+            let random_y = rand_gen.gen_range(SPAWN_Y_RANGE);
+            let random_starting_vel =
+                Vec2::new(rand_gen.gen_range(-1.0..1.0), rand_gen.gen_range(-1.0..1.0)).normalize();
+            let random_starting_speed = rand_gen.gen_range(1.0..KRILL_MAX_SPEED);
+
+            KrillBundle::new(
+                image_assets,
+                Vec2::new(random_x, random_y),
+                random_starting_vel * random_starting_speed,
+            )
+        })
+        .collect()
+}
+
 pub fn krill_idle_movement(mut krill_query: Query<&mut Transform, With<Krill>>, time: Res<Time>) {
     for mut krill_transform in krill_query.iter_mut() {
         const IDLE_HIEGHT_SCALAR: f32 = 0.005;
@@ -244,6 +385,7 @@ pub fn krill_update_velocity(
 pub fn boid_align(
     mut boid_a_query: Query<(Entity, &Transform, &Velocity, &mut Align), With<Boid>>,
     boid_b_query: Query<(Entity, &Transform, &Velocity), With<Boid>>,
+    tuning: Res<SwarmTuning>,
 ) {
     for (boid_entity_a, boid_transform_a, boid_velocity_a, mut boid_align_a) in
         boid_a_query.iter_mut()
@@ -259,7 +401,7 @@ pub fn boid_align(
             if boid_transform_a
                 .translation
                 .distance(boid_transform_b.translation)
-                < BOID_PERCEPTION_RADIUS
+                < tuning.perception_radius
             {
                 boid_align_a.vec += boid_velocity_b.linvel;
                 num_near_boids += 1;
@@ -269,7 +411,7 @@ pub fn boid_align(
         if num_near_boids > 0 {
             boid_align_a.vec /= num_near_boids as f32;
             boid_align_a.vec =
-                boid_align_a.vec.normalize() * KRILL_MAX_SPEED - boid_velocity_a.linvel;
+                boid_align_a.vec.normalize() * tuning.max_speed - boid_velocity_a.linvel;
             boid_align_a.vec.clamp_length_max(BOID_MAX_FORCE);
         }
     }
@@ -278,6 +420,7 @@ pub fn boid_align(
 pub fn boid_seperation(
     mut boid_a_query: Query<(Entity, &Transform, &Velocity, &mut Seperation), With<Boid>>,
     boid_b_query: Query<(Entity, &Transform), With<Boid>>,
+    tuning: Res<SwarmTuning>,
 ) {
     for (boid_entity_a, boid_transform_a, boid_velocity_a, mut boid_sepreation_a) in
         boid_a_query.iter_mut()
@@ -294,7 +437,7 @@ pub fn boid_seperation(
                 .translation
                 .distance(boid_transform_b.translation);
 
-            if distance_between_boids < BOID_PERCEPTION_RADIUS / 1.5
+            if distance_between_boids < tuning.perception_radius / 1.5
                 && !(-ERROR_FROM_ZERO..=ERROR_FROM_ZERO).contains(&distance_between_boids)
             {
                 let mut distance_between_boids_as_vec =
@@ -308,7 +451,7 @@ pub fn boid_seperation(
         if num_near_boids > 0 {
             boid_sepreation_a.vec /= num_near_boids as f32;
             boid_sepreation_a.vec =
-                boid_sepreation_a.vec.normalize() * KRILL_MAX_SPEED * 1.5 - boid_velocity_a.linvel;
+                boid_sepreation_a.vec.normalize() * tuning.max_speed * 1.5 - boid_velocity_a.linvel;
             boid_sepreation_a.vec.clamp_length_max(BOID_MAX_FORCE);
         }
     }
@@ -317,6 +460,7 @@ pub fn boid_seperation(
 pub fn boid_cohesion(
     mut boid_a_query: Query<(Entity, &Transform, &Velocity, &mut Cohesion), With<Boid>>,
     boid_b_query: Query<(Entity, &Transform), With<Boid>>,
+    tuning: Res<SwarmTuning>,
 ) {
     for (boid_entity_a, boid_transform_a, boid_velocity_a, mut boid_coehesion_a) in
         boid_a_query.iter_mut()
@@ -332,7 +476,7 @@ pub fn boid_cohesion(
             if boid_transform_a
                 .translation
                 .distance(boid_transform_b.translation)
-                < BOID_PERCEPTION_RADIUS * 1.1
+                < tuning.perception_radius * 1.1
             {
                 boid_coehesion_a.vec += boid_transform_b.translation.xy();
                 num_near_boids += 1;
@@ -343,7 +487,7 @@ pub fn boid_cohesion(
             boid_coehesion_a.vec /= num_near_boids as f32;
             boid_coehesion_a.vec = (boid_coehesion_a.vec - boid_transform_a.translation.xy())
                 .normalize()
-                * KRILL_MAX_SPEED
+                * tuning.max_speed
                 - boid_velocity_a.linvel;
             boid_coehesion_a.vec.clamp_length_max(BOID_MAX_FORCE);
         }
@@ -405,22 +549,28 @@ pub fn boid_flock(
 
 pub fn krill_avoid_player(
     mut krill_query: Query<(&mut Acceleration, &Transform), With<Krill>>,
-    player_query: Query<&Transform, With<Player>>,
+    player_query: Query<(&Transform, &Boost, &Growth), With<Whale>>,
+    tuning: Res<SwarmTuning>,
 ) {
-    let Ok(player_transform) = player_query.get_single() else {
-        info!("error");
-        return;
-    };
-    for (mut krill_acceleration, krill_transform) in krill_query.iter_mut() {
-        let dist = krill_transform
-            .translation
-            .distance(player_transform.translation);
-        if dist < BOID_PERCEPTION_RADIUS * 5. && dist > ERROR_FROM_ZERO {
-            krill_acceleration.vec += ((krill_transform.translation.xy()
-                - player_transform.translation.xy())
-            .normalize()
-                * KRILL_AVOIDANCE_MAG)
-                / ((dist / 30.).powf(1.3));
+    for (player_transform, player_boost, player_growth) in player_query.iter() {
+        let (radius_scale, mag_scale) = if player_boost.active {
+            (tuning.boost_panic_radius, tuning.boost_panic_mag)
+        } else {
+            (1., 1.)
+        };
+        let radius_scale = radius_scale * player_growth.fear_radius;
+        for (mut krill_acceleration, krill_transform) in krill_query.iter_mut() {
+            let dist = krill_transform
+                .translation
+                .distance(player_transform.translation);
+            if dist < tuning.perception_radius * 5. * radius_scale && dist > ERROR_FROM_ZERO {
+                krill_acceleration.vec += ((krill_transform.translation.xy()
+                    - player_transform.translation.xy())
+                .normalize()
+                    * tuning.avoidance_mag
+                    * mag_scale)
+                    / ((dist / 30.).powf(1.3));
+            }
         }
     }
 }
@@ -428,6 +578,7 @@ pub fn krill_avoid_player(
 pub fn krill_avoid_obstical(
     mut krill_query: Query<(&mut Acceleration, &Transform), With<Krill>>,
     obstacal_query: Query<&Obstacal, With<Obstacal>>,
+    tuning: Res<SwarmTuning>,
 ) {
     for (mut krill_acceleration, krill_transform) in krill_query.iter_mut() {
         for obstacal in obstacal_query.iter() {
@@ -440,7 +591,7 @@ pub fn krill_avoid_obstical(
 
             dist = dist.max(ERROR_FROM_ZERO);
 
-            if dist < BOID_PERCEPTION_RADIUS * 10. {
+            if dist < tuning.perception_radius * 10. {
                 let correction_vec = match obstacal {
                     Obstacal::Ceiling => -Vec2::Y,
                     Obstacal::RightWall => -Vec2::X,
@@ -449,8 +600,100 @@ pub fn krill_avoid_obstical(
                 };
 
                 krill_acceleration.vec +=
-                    (correction_vec * KRILL_AVOIDANCE_MAG * 10.) / ((dist).powf(0.7));
+                    (correction_vec * tuning.avoidance_mag * 10.) / ((dist).powf(0.7));
             }
         }
     }
 }
+
+pub fn krill_gulp_suction(
+    mut krill_query: Query<(&mut Acceleration, &Transform), With<Krill>>,
+    mouth_query: Query<(&GlobalTransform, &Parent), With<Mouth>>,
+    whale_query: Query<(&Gulp, &Facing, &Growth), Without<Dying>>,
+) {
+    for (mouth_transform, parent) in mouth_query.iter() {
+        let Ok((gulp, facing, growth)) = whale_query.get(parent.get()) else {
+            continue;
+        };
+        if !gulp.active {
+            continue;
+        }
+        let mouth = mouth_transform.translation().xy();
+
+        for (mut krill_acceleration, krill_transform) in krill_query.iter_mut() {
+            let krill = krill_transform.translation.xy();
+            if gulp.reaches(mouth, facing.0, growth.size, krill) {
+                krill_acceleration.apply((mouth - krill).normalize_or_zero() * gulp.strength);
+            }
+        }
+    }
+}
+
+pub fn krill_avoid_sonar(
+    mut krill_query: Query<(&mut Acceleration, &Transform), With<Krill>>,
+    pulse_query: Query<&SonarPulse>,
+    tuning: Res<SwarmTuning>,
+) {
+    for pulse in pulse_query.iter() {
+        for (mut krill_acceleration, krill_transform) in krill_query.iter_mut() {
+            let krill = krill_transform.translation.xy();
+            if pulse.touches(krill) {
+                krill_acceleration.vec +=
+                    (krill - pulse.origin).normalize_or_zero() * tuning.sonar_startle_mag;
+            }
+        }
+    }
+}
+
+pub fn update_swarm_density(
+    time: Res<Time>,
+    level_bounds: Res<LevelBounds>,
+    mut density: ResMut<SwarmDensity>,
+    krill_query: Query<&Transform, With<Krill>>,
+    mut krill_eaten_events: EventReader<KrillEatenEvent>,
+    mut swarm_cleared_events: EventWriter<SwarmClearedEvent>,
+) {
+    for event in krill_eaten_events.read() {
+        if let Some(index) = density.cell_index(event.position) {
+            if let Some(eaten) = density.eaten.get_mut(index) {
+                *eaten += 1;
+            }
+        }
+    }
+    if !density.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let size = level_bounds.rect.size();
+    density.origin = level_bounds.rect.min;
+    density.columns = (size.x / density.cell_size).ceil().max(1.) as usize;
+    density.rows = (size.y / density.cell_size).ceil().max(1.) as usize;
+    let cell_count = density.columns * density.rows;
+    density.previous = std::mem::take(&mut density.counts);
+    density.counts.resize(cell_count, 0);
+
+    for krill_transform in krill_query.iter() {
+        if let Some(index) = density.cell_index(krill_transform.translation.xy()) {
+            density.counts[index] += 1;
+        }
+    }
+
+    // the grid only changes shape if the level does, and then there's nothing to compare
+    if density.previous.len() == cell_count && density.eaten.len() == cell_count {
+        // a swarm that swam into the next cell hasn't been cleared, only one that was eaten
+        for (((position, count), previous), eaten) in
+            density.cells().zip(&density.previous).zip(&density.eaten)
+        {
+            if count == 0 && *previous >= SWARM_CLEARED_MIN && eaten >= previous {
+                swarm_cleared_events.send(SwarmClearedEvent { position });
+            }
+        }
+    }
+    density.eaten.clear();
+    density.eaten.resize(cell_count, 0);
+}
+
+/// Forgets the last run's grid, so its swarms aren't compared against this run's.
+pub fn reset_swarm_density(mut density: ResMut<SwarmDensity>) {
+    *density = SwarmDensity::default();
+}
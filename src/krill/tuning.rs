@@ -0,0 +1,433 @@
+use std::collections::VecDeque;
+
+use bevy::{ecs::system::RunSystemOnce, prelude::*, reflect::GetField};
+use bevy_inspector_egui::{
+    bevy_egui::{EguiContexts, EguiPlugin},
+    egui,
+};
+use bevy_rapier2d::dynamics::Velocity;
+use rand::random;
+use serde::{Deserialize, Serialize};
+
+use super::systems::{
+    scatter_krill, spawn_krill, AlignCoe, CohesionCoe, Krill, KrillState, RunSeed, SeperationCoe,
+    SwarmTuning,
+};
+use crate::{
+    assets::ImageAssets,
+    console::{parse_arg, require_run, AddConsoleCommand, ConsoleCommand},
+    in_gameplay,
+    settings::Settings,
+    storage,
+};
+
+// samples kept for each plot
+const HISTORY_LEN: usize = 120;
+const SAMPLE_INTERVAL: f32 = 0.25;
+const PLOT_HEIGHT: f32 = 40.0;
+const DEFAULT_PRESET: &str = "default";
+const TOGGLE_KEY: KeyCode = KeyCode::F6;
+// the flocking weights, then every `SwarmTuning` field
+const TUNABLES: [&str; 9] = [
+    "align",
+    "seperation",
+    "cohesion",
+    "perception_radius",
+    "max_speed",
+    "avoidance_mag",
+    "boost_panic_radius",
+    "boost_panic_mag",
+    "sonar_startle_mag",
+];
+
+/// A debug window for tuning the krill swarm while it runs. F6 hides and shows it.
+pub struct SwarmTuningPlugin;
+
+impl Plugin for SwarmTuningPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<EguiPlugin>() {
+            app.add_plugins(EguiPlugin);
+        }
+        app.init_resource::<SwarmStats>()
+            .init_resource::<TuningPanel>()
+            .add_console_command(SpawnCommand)
+            .add_console_command(SetCommand)
+            .add_console_command(SeedCommand)
+            .add_systems(
+                Update,
+                (
+                    toggle_tuning_panel,
+                    // the stats only feed the panel's plots
+                    (sample_swarm_stats, tuning_panel)
+                        .chain()
+                        .run_if(tuning_panel_open),
+                )
+                    .chain()
+                    .run_if(in_gameplay),
+            );
+    }
+}
+
+/// Every tunable swarm value, as saved to a preset.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct SwarmPreset {
+    pub align: f32,
+    pub seperation: f32,
+    pub cohesion: f32,
+    pub tuning: SwarmTuning,
+}
+
+/// Recent measurements of the swarm, oldest first.
+#[derive(Resource, Debug)]
+pub struct SwarmStats {
+    pub average_speed: VecDeque<f32>,
+    pub average_neighbors: VecDeque<f32>,
+    pub clusters: VecDeque<f32>,
+    pub timer: Timer,
+}
+
+impl Default for SwarmStats {
+    fn default() -> Self {
+        Self {
+            average_speed: VecDeque::with_capacity(HISTORY_LEN),
+            average_neighbors: VecDeque::with_capacity(HISTORY_LEN),
+            clusters: VecDeque::with_capacity(HISTORY_LEN),
+            timer: Timer::from_seconds(SAMPLE_INTERVAL, TimerMode::Repeating),
+        }
+    }
+}
+
+#[derive(Resource, Debug)]
+pub struct TuningPanel {
+    pub open: bool,
+    pub preset_name: String,
+    /// how the last save or load went
+    pub status: String,
+}
+
+impl Default for TuningPanel {
+    fn default() -> Self {
+        Self {
+            open: true,
+            preset_name: DEFAULT_PRESET.to_string(),
+            status: String::new(),
+        }
+    }
+}
+
+fn push_sample(history: &mut VecDeque<f32>, value: f32) {
+    if history.len() == HISTORY_LEN {
+        history.pop_front();
+    }
+    history.push_back(value);
+}
+
+// Neighbours are krill within perception range of each other; a cluster is a group of krill
+// linked by chains of neighbours.
+fn sample_swarm_stats(
+    time: Res<Time>,
+    tuning: Res<SwarmTuning>,
+    mut stats: ResMut<SwarmStats>,
+    krill_query: Query<(&Transform, &Velocity), With<Krill>>,
+) {
+    if !stats.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let krill: Vec<(Vec2, f32)> = krill_query
+        .iter()
+        .map(|(transform, velocity)| (transform.translation.xy(), velocity.linvel.length()))
+        .collect();
+    let count = krill.len();
+
+    // union-find over neighbour pairs
+    let mut parent: Vec<usize> = (0..count).collect();
+    fn root(parent: &mut [usize], mut index: usize) -> usize {
+        while parent[index] != index {
+            parent[index] = parent[parent[index]];
+            index = parent[index];
+        }
+        index
+    }
+
+    let mut neighbor_pairs = 0;
+    for a in 0..count {
+        for b in a + 1..count {
+            if krill[a].0.distance(krill[b].0) < tuning.perception_radius {
+                neighbor_pairs += 1;
+                let (root_a, root_b) = (root(&mut parent, a), root(&mut parent, b));
+                parent[root_a] = root_b;
+            }
+        }
+    }
+    let clusters = (0..count)
+        .filter(|&index| root(&mut parent, index) == index)
+        .count();
+
+    let (average_speed, average_neighbors) = if count == 0 {
+        (0.0, 0.0)
+    } else {
+        let total_speed: f32 = krill.iter().map(|(_, speed)| speed).sum();
+        (
+            total_speed / count as f32,
+            // each pair is a neighbour to both of its krill
+            (neighbor_pairs * 2) as f32 / count as f32,
+        )
+    };
+    push_sample(&mut stats.average_speed, average_speed);
+    push_sample(&mut stats.average_neighbors, average_neighbors);
+    push_sample(&mut stats.clusters, clusters as f32);
+}
+
+fn plot(ui: &mut egui::Ui, label: &str, history: &VecDeque<f32>) {
+    let latest = history.back().copied().unwrap_or(0.0);
+    ui.label(format!("{label}: {latest:.1}"));
+
+    let (response, painter) = ui.allocate_painter(
+        egui::vec2(ui.available_width(), PLOT_HEIGHT),
+        egui::Sense::hover(),
+    );
+    let rect = response.rect;
+    painter.rect_filled(rect, 0.0, egui::Color32::from_black_alpha(120));
+
+    let max = history.iter().copied().fold(f32::EPSILON, f32::max);
+    let step = rect.width() / (HISTORY_LEN - 1) as f32;
+    let points: Vec<egui::Pos2> = history
+        .iter()
+        .enumerate()
+        .map(|(index, value)| {
+            egui::pos2(
+                rect.left() + index as f32 * step,
+                rect.bottom() - value / max * rect.height(),
+            )
+        })
+        .collect();
+    painter.add(egui::Shape::line(
+        points,
+        egui::Stroke::new(1.5, egui::Color32::LIGHT_GREEN),
+    ));
+}
+
+// Presets are files on native, so the name is kept to characters that can't leave the save
+// directory.
+fn preset_save_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '_' || *c == '-')
+        .collect();
+    if name.is_empty() {
+        format!("swarm_preset_{DEFAULT_PRESET}")
+    } else {
+        format!("swarm_preset_{name}")
+    }
+}
+
+fn tuning_panel_open(panel: Res<TuningPanel>) -> bool {
+    panel.open
+}
+
+fn toggle_tuning_panel(keyboard_input: Res<Input<KeyCode>>, mut panel: ResMut<TuningPanel>) {
+    if keyboard_input.just_pressed(TOGGLE_KEY) {
+        panel.open = !panel.open;
+    }
+}
+
+fn tuning_panel(
+    mut commands: Commands,
+    mut contexts: EguiContexts,
+    mut panel: ResMut<TuningPanel>,
+    mut align: ResMut<AlignCoe>,
+    mut seperation: ResMut<SeperationCoe>,
+    mut cohesion: ResMut<CohesionCoe>,
+    mut tuning: ResMut<SwarmTuning>,
+    stats: Res<SwarmStats>,
+    krill_state: Res<State<KrillState>>,
+    mut next_krill_state: ResMut<NextState<KrillState>>,
+    krill_query: Query<Entity, With<Krill>>,
+) {
+    egui::Window::new("Swarm Tuning").show(contexts.ctx_mut(), |ui| {
+        ui.heading("Flocking");
+        ui.add(egui::Slider::new(&mut align.mag, 0.0..=20.0).text("align"));
+        ui.add(egui::Slider::new(&mut seperation.mag, 0.0..=20.0).text("seperation"));
+        ui.add(egui::Slider::new(&mut cohesion.mag, 0.0..=20.0).text("cohesion"));
+        ui.add(
+            egui::Slider::new(&mut tuning.perception_radius, 1.0..=30.0).text("perception radius"),
+        );
+        ui.add(egui::Slider::new(&mut tuning.max_speed, 1.0..=150.0).text("max speed"));
+
+        ui.heading("Fear");
+        ui.add(egui::Slider::new(&mut tuning.avoidance_mag, 0.0..=200.0).text("avoidance"));
+        ui.add(
+            egui::Slider::new(&mut tuning.boost_panic_radius, 1.0..=4.0).text("boost panic radius"),
+        );
+        ui.add(egui::Slider::new(&mut tuning.boost_panic_mag, 1.0..=5.0).text("boost panic"));
+        ui.add(
+            egui::Slider::new(&mut tuning.sonar_startle_mag, 0.0..=1000.0).text("sonar startle"),
+        );
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            if ui.button("Respawn swarm").clicked() {
+                for krill in &krill_query {
+                    commands.entity(krill).despawn_recursive();
+                }
+                commands.add(|world: &mut World| world.run_system_once(spawn_krill));
+            }
+            for (state, label) in [(KrillState::Moving, "Moving"), (KrillState::Idle, "Idle")] {
+                if ui
+                    .selectable_label(*krill_state.get() == state, label)
+                    .clicked()
+                {
+                    next_krill_state.set(state);
+                }
+            }
+        });
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label("Preset");
+            ui.text_edit_singleline(&mut panel.preset_name);
+        });
+        ui.horizontal(|ui| {
+            if ui.button("Save").clicked() {
+                let preset = SwarmPreset {
+                    align: align.mag,
+                    seperation: seperation.mag,
+                    cohesion: cohesion.mag,
+                    tuning: tuning.clone(),
+                };
+                panel.status = match ron::to_string(&preset)
+                    .map_err(|error| error.to_string())
+                    .and_then(|serialized| {
+                        storage::write(&preset_save_name(&panel.preset_name), &serialized)
+                    }) {
+                    Ok(()) => format!("saved {}", panel.preset_name),
+                    Err(error) => format!("could not save: {error}"),
+                };
+            }
+            if ui.button("Load").clicked() {
+                let loaded = storage::read(&preset_save_name(&panel.preset_name))
+                    .ok_or_else(|| "no such preset".to_string())
+                    .and_then(|saved| {
+                        ron::from_str::<SwarmPreset>(&saved).map_err(|error| error.to_string())
+                    });
+                panel.status = match loaded {
+                    Ok(preset) => {
+                        align.mag = preset.align;
+                        seperation.mag = preset.seperation;
+                        cohesion.mag = preset.cohesion;
+                        *tuning = preset.tuning;
+                        format!("loaded {}", panel.preset_name)
+                    }
+                    Err(error) => format!("could not load: {error}"),
+                };
+            }
+            if ui.button("Defaults").clicked() {
+                *align = AlignCoe::default();
+                *seperation = SeperationCoe::default();
+                *cohesion = CohesionCoe::default();
+                *tuning = SwarmTuning::default();
+            }
+        });
+        if !panel.status.is_empty() {
+            ui.label(&panel.status);
+        }
+
+        ui.separator();
+        plot(ui, "average speed", &stats.average_speed);
+        plot(ui, "average neighbours", &stats.average_neighbors);
+        plot(ui, "clusters", &stats.clusters);
+    });
+}
+
+struct SpawnCommand;
+
+impl ConsoleCommand for SpawnCommand {
+    fn name(&self) -> &'static str {
+        "spawn"
+    }
+
+    fn usage(&self) -> &'static str {
+        "krill <count>"
+    }
+
+    fn arguments(&self) -> &[&'static str] {
+        &["krill"]
+    }
+
+    fn run(&self, args: &[&str], world: &mut World) -> Result<String, String> {
+        require_run(world)?;
+        match args.first() {
+            Some(&"krill") => {}
+            Some(other) => return Err(format!("can't spawn {other}")),
+            None => return Err("missing what to spawn".to_string()),
+        }
+        let count: usize = parse_arg(args, 1, "count")?;
+        // added to the swarm, so the run keeps its seed
+        let krill = scatter_krill(world.resource::<ImageAssets>(), count, random());
+        world.spawn_batch(krill);
+        Ok(format!("spawned {count} krill"))
+    }
+}
+
+struct SetCommand;
+
+impl ConsoleCommand for SetCommand {
+    fn name(&self) -> &'static str {
+        "set"
+    }
+
+    fn usage(&self) -> &'static str {
+        "<tunable> <value>"
+    }
+
+    fn arguments(&self) -> &[&'static str] {
+        &TUNABLES
+    }
+
+    fn run(&self, args: &[&str], world: &mut World) -> Result<String, String> {
+        let name = *args.first().ok_or("missing tunable")?;
+        let value: f32 = parse_arg(args, 1, "value")?;
+        match name {
+            "align" => world.resource_mut::<AlignCoe>().mag = value,
+            "seperation" => world.resource_mut::<SeperationCoe>().mag = value,
+            "cohesion" => world.resource_mut::<CohesionCoe>().mag = value,
+            field => {
+                let mut tuning = world.resource_mut::<SwarmTuning>();
+                *tuning
+                    .get_field_mut::<f32>(field)
+                    .ok_or_else(|| format!("no tunable called {field}"))? = value;
+            }
+        }
+        Ok(format!("{name} = {value}"))
+    }
+}
+
+struct SeedCommand;
+
+impl ConsoleCommand for SeedCommand {
+    fn name(&self) -> &'static str {
+        "seed"
+    }
+
+    fn usage(&self) -> &'static str {
+        "<seed>"
+    }
+
+    fn run(&self, args: &[&str], world: &mut World) -> Result<String, String> {
+        require_run(world)?;
+        let seed: u64 = parse_arg(args, 0, "seed")?;
+        let old_krill: Vec<Entity> = world
+            .query_filtered::<Entity, With<Krill>>()
+            .iter(world)
+            .collect();
+        for krill in old_krill {
+            despawn_with_children_recursive(world, krill);
+        }
+        let count = world.resource::<Settings>().krill_quality.krill_count();
+        let krill = scatter_krill(world.resource::<ImageAssets>(), count, seed);
+        world.spawn_batch(krill);
+        world.insert_resource(RunSeed(seed));
+        Ok(format!("rescattered the swarm with seed {seed}"))
+    }
+}
@@ -0,0 +1,270 @@
+use bevy::{
+    asset::{LoadState, UntypedHandle},
+    prelude::*,
+};
+use bevy_asset_loader::{
+    asset_collection::AssetCollection,
+    loading_state::{LoadingState, LoadingStateAppExt},
+};
+
+use crate::{despawn::StateScoped, GameState};
+
+// the splash stays up at least this long, even when everything loads at once
+const SPLASH_MIN_SECONDS: f32 = 1.5;
+const PROGRESS_BAR_WIDTH: f32 = 300.0;
+const PROGRESS_BAR_HEIGHT: f32 = 16.0;
+
+pub struct LoadingPlugin;
+
+impl Plugin for LoadingPlugin {
+    fn build(&self, app: &mut App) {
+        // no `continue_to_state`: `finish_loading` moves on, once the splash has been up long enough
+        app.add_loading_state(
+            LoadingState::new(GameState::Loading)
+                .on_failure_continue_to_state(GameState::LoadingError),
+        )
+        .init_resource::<LoadingProgress>()
+        .add_systems(OnEnter(GameState::Loading), show_loading_screen)
+        .add_systems(OnEnter(GameState::LoadingError), show_loading_error)
+        .add_systems(
+            Update,
+            (update_progress_bar, finish_loading).run_if(in_state(GameState::Loading)),
+        );
+    }
+}
+
+/// Adds asset collections to the loading state, with their progress shown on the loading screen.
+pub trait TrackedCollectionAppExt {
+    fn add_tracked_collection<T: AssetCollection>(&mut self) -> &mut Self;
+}
+
+impl TrackedCollectionAppExt for App {
+    fn add_tracked_collection<T: AssetCollection>(&mut self) -> &mut Self {
+        self.add_collection_to_loading_state::<_, T>(GameState::Loading)
+            .add_systems(OnEnter(GameState::Loading), track_collection::<T>)
+            .add_systems(
+                Update,
+                collection_ready::<T>
+                    .before(finish_loading)
+                    .run_if(in_state(GameState::Loading)),
+            )
+    }
+}
+
+/// Every handle the loading state is waiting on, across all tracked collections.
+#[derive(Resource, Debug)]
+pub struct LoadingProgress {
+    pub handles: Vec<UntypedHandle>,
+    /// collections that have not yet been built from their loaded assets
+    pub unfinished: usize,
+    pub splash: Timer,
+}
+
+impl Default for LoadingProgress {
+    fn default() -> Self {
+        Self {
+            handles: Vec::new(),
+            unfinished: 0,
+            splash: Timer::from_seconds(SPLASH_MIN_SECONDS, TimerMode::Once),
+        }
+    }
+}
+
+impl LoadingProgress {
+    pub fn loaded(&self, asset_server: &AssetServer) -> usize {
+        self.handles
+            .iter()
+            .filter(|handle| asset_server.is_loaded_with_dependencies(handle.id()))
+            .count()
+    }
+
+    pub fn failed<'a>(
+        &'a self,
+        asset_server: &'a AssetServer,
+    ) -> impl Iterator<Item = &'a UntypedHandle> + 'a {
+        self.handles
+            .iter()
+            .filter(|handle| asset_server.get_load_state(handle.id()) == Some(LoadState::Failed))
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct ProgressBar;
+
+#[derive(Component, Debug)]
+pub struct ProgressText;
+
+// The asset server hands back the handles the loading state already asked for, so asking again
+// here only tells us what to watch.
+fn track_collection<T: AssetCollection>(world: &mut World) {
+    let handles = T::load(world);
+    let mut progress = world.resource_mut::<LoadingProgress>();
+    progress.handles.extend(handles);
+    progress.unfinished += 1;
+}
+
+fn collection_ready<T: AssetCollection>(
+    collection: Option<Res<T>>,
+    mut counted: Local<bool>,
+    mut progress: ResMut<LoadingProgress>,
+) {
+    if !*counted && collection.is_some() {
+        *counted = true;
+        progress.unfinished -= 1;
+    }
+}
+
+fn show_loading_screen(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(10.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            StateScoped(GameState::Loading),
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Krilling With Cthulhu",
+                TextStyle {
+                    font_size: 60.0,
+                    color: Color::AQUAMARINE,
+                    ..default()
+                },
+            ));
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(PROGRESS_BAR_WIDTH),
+                        height: Val::Px(PROGRESS_BAR_HEIGHT),
+                        ..default()
+                    },
+                    background_color: Color::rgba(0.0, 0.0, 0.0, 0.5).into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn((
+                        NodeBundle {
+                            style: Style {
+                                width: Val::Percent(0.0),
+                                height: Val::Percent(100.0),
+                                ..default()
+                            },
+                            background_color: Color::AQUAMARINE.into(),
+                            ..default()
+                        },
+                        ProgressBar,
+                    ));
+                });
+            parent.spawn((
+                TextBundle::from_section(
+                    "Loading...",
+                    TextStyle {
+                        font_size: 20.0,
+                        ..default()
+                    },
+                ),
+                ProgressText,
+            ));
+        });
+}
+
+fn update_progress_bar(
+    asset_server: Res<AssetServer>,
+    progress: Res<LoadingProgress>,
+    mut bar_query: Query<&mut Style, With<ProgressBar>>,
+    mut text_query: Query<&mut Text, With<ProgressText>>,
+) {
+    let total = progress.handles.len();
+    let loaded = progress.loaded(&asset_server);
+    let fraction = if total == 0 {
+        0.0
+    } else {
+        loaded as f32 / total as f32
+    };
+    for mut style in &mut bar_query {
+        style.width = Val::Percent(fraction * 100.0);
+    }
+    for mut text in &mut text_query {
+        text.sections[0].value = format!("Loading... {loaded} / {total}");
+    }
+}
+
+fn finish_loading(
+    time: Res<Time>,
+    mut progress: ResMut<LoadingProgress>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let splash_done = progress.splash.tick(time.delta()).finished();
+    if splash_done && progress.unfinished == 0 && !progress.handles.is_empty() {
+        next_state.set(GameState::MainMenu);
+    }
+}
+
+fn show_loading_error(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    progress: Res<LoadingProgress>,
+) {
+    let failed: Vec<String> = progress
+        .failed(&asset_server)
+        .map(|handle| match handle.path() {
+            Some(path) => path.to_string(),
+            None => format!("{:?}", handle.id()),
+        })
+        .collect();
+    for path in &failed {
+        error!("failed to load asset {path}");
+    }
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(10.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            StateScoped(GameState::LoadingError),
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Something went wrong loading the game",
+                TextStyle {
+                    font_size: 40.0,
+                    color: Color::CRIMSON,
+                    ..default()
+                },
+            ));
+            for path in failed {
+                parent.spawn(TextBundle::from_section(
+                    format!("Could not load {path}"),
+                    TextStyle {
+                        font_size: 20.0,
+                        ..default()
+                    },
+                ));
+            }
+            parent.spawn(TextBundle::from_section(
+                "Check the assets folder is next to the game, then restart. Esc to quit.",
+                TextStyle {
+                    font_size: 20.0,
+                    ..default()
+                },
+            ));
+        });
+}
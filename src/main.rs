@@ -7,29 +7,96 @@
 #![allow(non_snake_case)]
 
 mod assets;
+mod boss;
+mod camera;
+#[cfg(feature = "debug")]
+mod console;
+mod despawn;
 mod display;
+mod fish;
+mod growth;
+mod health;
+mod highscores;
+mod hud;
 mod krill;
+mod loading;
 mod map;
+mod menu;
+mod minimap;
 mod physics;
 mod player;
+mod popups;
+mod rival;
+mod scoring;
+mod settings;
+mod sonar;
+mod storage;
+mod tentacle;
+mod tint;
 
 use assets::AssetsPlugin;
-use bevy::{prelude::*, render::camera::ScalingMode};
-use bevy_asset_loader::loading_state::{LoadingState, LoadingStateAppExt};
+use bevy::prelude::*;
+use boss::BossPlugin;
+use camera::CameraPlugin;
+use despawn::DespawnPlugin;
 use display::DisplayPlugin;
+use fish::FishPlugin;
+use growth::GrowthPlugin;
+use health::HealthPlugin;
+use highscores::HighScoresPlugin;
+use hud::HudPlugin;
 use krill::KrillPlugin;
+use loading::LoadingPlugin;
 use map::MapPlugin;
+use menu::MenuPlugin;
+use minimap::MinimapPlugin;
 use physics::PhysicsPlugin;
 use player::PlayerPlugin;
+use popups::PopupsPlugin;
+use rival::RivalPlugin;
+use scoring::ScoringPlugin;
+use settings::{Settings, SettingsPlugin};
+use sonar::SonarPlugin;
+use tentacle::TentaclePlugin;
+use tint::TintPlugin;
 
 #[cfg(feature = "debug")]
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
+#[cfg(feature = "debug")]
+use console::ConsolePlugin;
+#[cfg(feature = "debug")]
+use krill::{overlay::FlockingOverlayPlugin, tuning::SwarmTuningPlugin};
+#[cfg(feature = "debug")]
+use physics::debug::PhysicsDebugPlugin;
 
 #[derive(Clone, Eq, PartialEq, Debug, Hash, Default, States)]
 pub enum GameState {
     #[default]
     Loading,
-    Active,
+    /// an asset failed to load; shows what and waits for the player to quit
+    LoadingError,
+    MainMenu,
+    Playing,
+    /// the Cthulhu encounter, played on the same level as `Playing`
+    BossFight,
+    /// entered from `Playing` or `BossFight`, and returns to whichever it came from
+    Paused,
+    GameOver,
+    Victory,
+    Leaderboard,
+    Settings,
+}
+
+/// Entered once at the start of every run; spawn what a run needs here rather than in
+/// `OnEnter(Playing)`, which also fires when resuming from `Paused`.
+pub const START_RUN: OnTransition<GameState> = OnTransition {
+    from: GameState::MainMenu,
+    to: GameState::Playing,
+};
+
+/// Run condition for systems that belong to any part of a run, boss fight included.
+pub fn in_gameplay(state: Res<State<GameState>>) -> bool {
+    matches!(state.get(), GameState::Playing | GameState::BossFight)
 }
 
 #[derive(Event)]
@@ -39,39 +106,54 @@ pub struct DebugEvent;
 ///
 /// Requires the feature '2d'
 fn main() {
+    // read first, the window is made with them
+    let settings = Settings::load();
     let mut app = App::new();
     app.add_state::<GameState>()
-        .add_loading_state(
-            LoadingState::new(GameState::Loading).continue_to_state(GameState::Active),
-        )
-        .add_plugins(DefaultPlugins)
+        .add_plugins(DefaultPlugins.set(WindowPlugin {
+            primary_window: Some(settings.window()),
+            ..default()
+        }))
+        .insert_resource(settings)
+        .add_plugins(SettingsPlugin)
+        .add_plugins(LoadingPlugin)
         // Main Plugins
+        .add_plugins(CameraPlugin)
         .add_plugins(DisplayPlugin)
+        .add_plugins(MenuPlugin)
         .add_plugins(PhysicsPlugin)
         .add_plugins(AssetsPlugin)
         .add_plugins(PlayerPlugin)
         .add_plugins(KrillPlugin)
         .add_plugins(MapPlugin)
+        .add_plugins(DespawnPlugin)
+        .add_plugins(HealthPlugin)
+        .add_plugins(GrowthPlugin)
+        .add_plugins(SonarPlugin)
+        .add_plugins(TintPlugin)
+        .add_plugins(FishPlugin)
+        .add_plugins(RivalPlugin)
+        .add_plugins(BossPlugin)
+        .add_plugins(TentaclePlugin)
+        .add_plugins(ScoringPlugin)
+        .add_plugins(HighScoresPlugin)
+        .add_plugins(HudPlugin)
+        .add_plugins(PopupsPlugin)
+        .add_plugins(MinimapPlugin)
         .add_event::<DebugEvent>()
-        .add_systems(Startup, setup)
-        .add_systems(Update, (debug, bevy::window::close_on_esc));
+        .add_systems(Update, debug);
 
     // Development Plugins
     #[cfg(feature = "debug")]
-    app.add_plugins(WorldInspectorPlugin::new());
+    app.add_plugins(WorldInspectorPlugin::new())
+        .add_plugins(SwarmTuningPlugin)
+        .add_plugins(FlockingOverlayPlugin)
+        .add_plugins(PhysicsDebugPlugin)
+        .add_plugins(ConsolePlugin);
 
     app.run();
 }
 
-fn setup(mut commands: Commands) {
-    let mut camera = Camera2dBundle::default();
-    camera.projection.scaling_mode = ScalingMode::AutoMin {
-        min_width: 256.,
-        min_height: 144.,
-    };
-    commands.spawn(camera);
-}
-
 pub fn debug(keyboard_input: Res<Input<KeyCode>>, mut debug_event_writer: EventWriter<DebugEvent>) {
     if keyboard_input.just_pressed(KeyCode::Q) {
         debug_event_writer.send(DebugEvent);
@@ -6,11 +6,53 @@ pub const TOP_BORDER: f32 = 50.;
 pub const RIGHT_BORDER: f32 = 110.;
 pub const BOTTOM_BORDER: f32 = -50.;
 pub const LEFT_BORDER: f32 = -110.;
+// outer edges of the walls
+const LEVEL_HALF_WIDTH: f32 = 140.;
+const LEVEL_HALF_HEIGHT: f32 = 80.;
+// everything below this is deep water, too dark to see without sonar
+const DEEP_WATER_TOP: f32 = -20.;
 
 pub struct MapPlugin;
 impl Plugin for MapPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, (floor, left_wall, right_wall, ceiling));
+        app.register_type::<LevelBounds>()
+            .register_type::<DarkRegions>()
+            .init_resource::<LevelBounds>()
+            .init_resource::<DarkRegions>()
+            .add_systems(Startup, (floor, left_wall, right_wall, ceiling, dark_water));
+    }
+}
+
+/// The area covered by the current level, walls included.
+#[derive(Resource, Debug, Clone, Copy, Reflect)]
+#[reflect(Resource)]
+pub struct LevelBounds {
+    pub rect: Rect,
+}
+
+impl Default for LevelBounds {
+    fn default() -> Self {
+        Self {
+            rect: Rect::new(
+                -LEVEL_HALF_WIDTH,
+                -LEVEL_HALF_HEIGHT,
+                LEVEL_HALF_WIDTH,
+                LEVEL_HALF_HEIGHT,
+            ),
+        }
+    }
+}
+
+/// Parts of the level where concealable things can't be seen unless sonar reveals them.
+#[derive(Resource, Debug, Clone, Default, Reflect)]
+#[reflect(Resource)]
+pub struct DarkRegions {
+    pub rects: Vec<Rect>,
+}
+
+impl DarkRegions {
+    pub fn contains(&self, point: Vec2) -> bool {
+        self.rects.iter().any(|rect| rect.contains(point))
     }
 }
 
@@ -22,6 +64,31 @@ pub enum Obstacal {
     LeftWall,
 }
 
+pub fn dark_water(
+    mut commands: Commands,
+    mut dark_regions: ResMut<DarkRegions>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let rect = Rect::new(
+        -LEVEL_HALF_WIDTH,
+        -LEVEL_HALF_HEIGHT,
+        LEVEL_HALF_WIDTH,
+        DEEP_WATER_TOP,
+    );
+    dark_regions.rects.push(rect);
+    // drawn over the water but under the walls and everything swimming
+    commands.spawn((
+        Name::new("Dark Water"),
+        MaterialMesh2dBundle {
+            mesh: meshes.add(Mesh::from(shape::Quad::new(rect.size()))).into(),
+            material: materials.add(ColorMaterial::from(Color::rgba(0.0, 0.0, 0.05, 0.7))),
+            transform: Transform::from_translation(rect.center().extend(-2.0)),
+            ..default()
+        },
+    ));
+}
+
 pub fn floor(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
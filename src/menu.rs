@@ -0,0 +1,279 @@
+use bevy::{app::AppExit, prelude::*};
+use bevy_rapier2d::prelude::RapierConfiguration;
+
+use crate::{
+    despawn::StateScoped, highscores::NameEntry, rival::Difficulty, scoring::GameMode,
+    settings::Settings, GameState,
+};
+
+const TITLE_FONT_SIZE: f32 = 60.0;
+const HINT_FONT_SIZE: f32 = 25.0;
+
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ResumeState>()
+            .add_systems(OnEnter(GameState::MainMenu), show_main_menu)
+            .add_systems(OnEnter(GameState::Paused), (show_pause_menu, freeze))
+            .add_systems(OnExit(GameState::Paused), thaw)
+            .add_systems(OnEnter(GameState::GameOver), show_game_over)
+            .add_systems(OnEnter(GameState::Victory), show_victory)
+            .add_systems(
+                Update,
+                (
+                    navigate_menus,
+                    (
+                        choose_game_mode,
+                        update_mode_text,
+                        choose_difficulty,
+                        update_difficulty_text,
+                    )
+                        .chain()
+                        .run_if(in_state(GameState::MainMenu)),
+                ),
+            );
+    }
+}
+
+/// The state to go back to when the game is unpaused.
+#[derive(Resource, Debug, Clone)]
+pub struct ResumeState(pub GameState);
+
+impl Default for ResumeState {
+    fn default() -> Self {
+        Self(GameState::Playing)
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct ModeText;
+
+#[derive(Component, Debug)]
+pub struct DifficultyText;
+
+fn show_main_menu(
+    mut commands: Commands,
+    mode: Res<GameMode>,
+    difficulty: Res<Difficulty>,
+    settings: Res<Settings>,
+) {
+    let tr = |text| settings.language.translate(text);
+    let screen = show_screen(
+        &mut commands,
+        GameState::MainMenu,
+        "Krilling With Cthulhu",
+        Color::AQUAMARINE,
+        tr("Enter to dive in, S for settings, L for high scores, Esc to quit"),
+    );
+    commands.entity(screen).with_children(|parent| {
+        parent.spawn((
+            TextBundle::from_section(
+                mode_label(*mode, &settings),
+                TextStyle {
+                    font_size: HINT_FONT_SIZE,
+                    color: Color::GOLD,
+                    ..default()
+                },
+            ),
+            ModeText,
+        ));
+        parent.spawn((
+            TextBundle::from_section(
+                difficulty_label(*difficulty, &settings),
+                TextStyle {
+                    font_size: HINT_FONT_SIZE,
+                    color: Color::GOLD,
+                    ..default()
+                },
+            ),
+            DifficultyText,
+        ));
+    });
+}
+
+fn mode_label(mode: GameMode, settings: &Settings) -> String {
+    let tr = |text| settings.language.translate(text);
+    format!("{}: {} ({})", tr("Mode"), mode.name(), tr("Tab to change"))
+}
+
+fn choose_game_mode(keyboard_input: Res<Input<KeyCode>>, mut mode: ResMut<GameMode>) {
+    if keyboard_input.just_pressed(KeyCode::Tab) {
+        *mode = mode.next();
+    }
+}
+
+fn update_mode_text(
+    mode: Res<GameMode>,
+    settings: Res<Settings>,
+    mut text_query: Query<&mut Text, With<ModeText>>,
+) {
+    if !mode.is_changed() {
+        return;
+    }
+    for mut text in &mut text_query {
+        text.sections[0].value = mode_label(*mode, &settings);
+    }
+}
+
+fn difficulty_label(difficulty: Difficulty, settings: &Settings) -> String {
+    let tr = |text| settings.language.translate(text);
+    format!(
+        "{}: {} ({})",
+        tr("Difficulty"),
+        tr(difficulty.name()),
+        tr("D to change")
+    )
+}
+
+fn choose_difficulty(keyboard_input: Res<Input<KeyCode>>, mut difficulty: ResMut<Difficulty>) {
+    if keyboard_input.just_pressed(KeyCode::D) {
+        *difficulty = difficulty.next();
+    }
+}
+
+fn update_difficulty_text(
+    difficulty: Res<Difficulty>,
+    settings: Res<Settings>,
+    mut text_query: Query<&mut Text, With<DifficultyText>>,
+) {
+    if !difficulty.is_changed() {
+        return;
+    }
+    for mut text in &mut text_query {
+        text.sections[0].value = difficulty_label(*difficulty, &settings);
+    }
+}
+
+fn show_pause_menu(mut commands: Commands, settings: Res<Settings>) {
+    let tr = |text| settings.language.translate(text);
+    show_screen(
+        &mut commands,
+        GameState::Paused,
+        tr("Paused"),
+        Color::WHITE,
+        tr("Esc to resume, M for the main menu"),
+    );
+}
+
+fn show_game_over(mut commands: Commands, settings: Res<Settings>) {
+    let tr = |text| settings.language.translate(text);
+    show_screen(
+        &mut commands,
+        GameState::GameOver,
+        tr("Game Over"),
+        Color::CRIMSON,
+        tr("Enter for the main menu"),
+    );
+}
+
+fn show_victory(mut commands: Commands, settings: Res<Settings>) {
+    let tr = |text| settings.language.translate(text);
+    show_screen(
+        &mut commands,
+        GameState::Victory,
+        tr("Cthulhu Sleeps Again"),
+        Color::GOLD,
+        tr("Enter for the main menu"),
+    );
+}
+
+fn show_screen(
+    commands: &mut Commands,
+    state: GameState,
+    title: &str,
+    color: Color,
+    hint: &str,
+) -> Entity {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            StateScoped(state),
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                title,
+                TextStyle {
+                    font_size: TITLE_FONT_SIZE,
+                    color,
+                    ..default()
+                },
+            ));
+            parent.spawn(TextBundle::from_section(
+                hint,
+                TextStyle {
+                    font_size: HINT_FONT_SIZE,
+                    ..default()
+                },
+            ));
+        })
+        .id()
+}
+
+// Esc or a gamepad's start button backs out of wherever the player is; Enter or start moves on.
+pub fn navigate_menus(
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepad_input: Res<Input<GamepadButton>>,
+    gamepads: Res<Gamepads>,
+    state: Res<State<GameState>>,
+    name_entry: Res<NameEntry>,
+    mut resume_state: ResMut<ResumeState>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let start = gamepads.iter().any(|gamepad| {
+        gamepad_input.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::Start))
+    });
+    let back = keyboard_input.just_pressed(KeyCode::Escape) || start;
+    let confirm = keyboard_input.just_pressed(KeyCode::Return) || start;
+
+    match state.get() {
+        GameState::MainMenu | GameState::LoadingError
+            if keyboard_input.just_pressed(KeyCode::Escape) =>
+        {
+            exit.send(AppExit);
+        }
+        GameState::MainMenu if confirm => next_state.set(GameState::Playing),
+        GameState::MainMenu if keyboard_input.just_pressed(KeyCode::L) => {
+            next_state.set(GameState::Leaderboard);
+        }
+        GameState::MainMenu if keyboard_input.just_pressed(KeyCode::S) => {
+            next_state.set(GameState::Settings);
+        }
+        // while a high score name is being typed, confirming belongs to the name entry
+        GameState::GameOver | GameState::Victory if confirm && !name_entry.pending => {
+            next_state.set(GameState::MainMenu);
+        }
+        GameState::Leaderboard if confirm || back => next_state.set(GameState::MainMenu),
+        GameState::Playing | GameState::BossFight if back => {
+            resume_state.0 = state.get().clone();
+            next_state.set(GameState::Paused);
+        }
+        GameState::Paused if back => next_state.set(resume_state.0.clone()),
+        GameState::Paused if keyboard_input.just_pressed(KeyCode::M) => {
+            next_state.set(GameState::MainMenu);
+        }
+        _ => {}
+    }
+}
+
+// Time stops for everything, Rapier included, until the game is unpaused.
+fn freeze(mut time: ResMut<Time<Virtual>>, mut rapier_config: ResMut<RapierConfiguration>) {
+    time.pause();
+    rapier_config.physics_pipeline_active = false;
+}
+
+fn thaw(mut time: ResMut<Time<Virtual>>, mut rapier_config: ResMut<RapierConfiguration>) {
+    time.unpause();
+    rapier_config.physics_pipeline_active = true;
+}
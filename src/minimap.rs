@@ -0,0 +1,246 @@
+use bevy::{
+    prelude::*,
+    render::{
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+        texture::ImageSampler,
+    },
+};
+use bevy_rapier2d::prelude::Collider;
+
+use crate::{
+    despawn::RunScoped,
+    fish::systems::Species,
+    health::Hazard,
+    in_gameplay,
+    krill::systems::SwarmDensity,
+    map::{LevelBounds, Obstacal},
+    player::{PlayerId, Whale},
+    START_RUN,
+};
+
+// minimap pixels per world unit
+const MINIMAP_RESOLUTION: f32 = 0.5;
+// on screen, in UI pixels per minimap pixel
+const MINIMAP_ZOOM: f32 = 1.5;
+// it only needs to keep up with the swarms, not every frame
+const MINIMAP_INTERVAL: f32 = 0.25;
+const MINIMAP_MARGIN: f32 = 5.0;
+const WATER_COLOR: [u8; 4] = [5, 20, 45, 200];
+const OBSTACLE_COLOR: [u8; 4] = [60, 70, 110, 255];
+const HEAT_COLOR: [u8; 3] = [255, 120, 170];
+const THREAT_COLOR: [u8; 4] = [230, 40, 40, 255];
+const RIVAL_COLOR: [u8; 4] = [180, 180, 180, 255];
+const THREAT_RADIUS: i32 = 1;
+const WHALE_RADIUS: i32 = 2;
+
+pub struct MinimapPlugin;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, create_minimap)
+            .add_systems(START_RUN, spawn_minimap)
+            .add_systems(Update, draw_minimap.run_if(in_gameplay));
+    }
+}
+
+/// The image the minimap is painted into, and when it is next due a repaint.
+#[derive(Resource, Debug)]
+pub struct Minimap {
+    pub image: Handle<Image>,
+    pub timer: Timer,
+}
+
+fn minimap_size(level_bounds: &LevelBounds) -> UVec2 {
+    (level_bounds.rect.size() * MINIMAP_RESOLUTION)
+        .ceil()
+        .max(Vec2::ONE)
+        .as_uvec2()
+}
+
+fn create_minimap(
+    mut commands: Commands,
+    level_bounds: Res<LevelBounds>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let size = minimap_size(&level_bounds);
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: size.x,
+            height: size.y,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &WATER_COLOR,
+        TextureFormat::Rgba8UnormSrgb,
+    );
+    // chunky pixels rather than a blur
+    image.sampler = ImageSampler::nearest();
+    commands.insert_resource(Minimap {
+        image: images.add(image),
+        timer: Timer::from_seconds(MINIMAP_INTERVAL, TimerMode::Repeating),
+    });
+}
+
+// Along the bottom of the screen, between the player panels.
+fn spawn_minimap(mut commands: Commands, minimap: Res<Minimap>, level_bounds: Res<LevelBounds>) {
+    let size = minimap_size(&level_bounds).as_vec2() * MINIMAP_ZOOM;
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    bottom: Val::Px(MINIMAP_MARGIN),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            RunScoped,
+        ))
+        .with_children(|parent| {
+            parent.spawn(ImageBundle {
+                style: Style {
+                    width: Val::Px(size.x),
+                    height: Val::Px(size.y),
+                    ..default()
+                },
+                image: minimap.image.clone().into(),
+                ..default()
+            });
+        });
+}
+
+/// Paints into an RGBA image, with world positions mapped onto it through the level bounds.
+struct Canvas<'a> {
+    image: &'a mut Image,
+    bounds: Rect,
+    size: IVec2,
+}
+
+impl Canvas<'_> {
+    fn pixel(&self, position: Vec2) -> IVec2 {
+        let pixel = ((position - self.bounds.min) * MINIMAP_RESOLUTION)
+            .floor()
+            .as_ivec2();
+        // images count rows from the top
+        IVec2::new(pixel.x, self.size.y - 1 - pixel.y)
+    }
+
+    fn set(&mut self, pixel: IVec2, color: [u8; 4]) {
+        if pixel.x < 0 || pixel.y < 0 || pixel.x >= self.size.x || pixel.y >= self.size.y {
+            return;
+        }
+        let index = (pixel.y * self.size.x + pixel.x) as usize * 4;
+        self.image.data[index..index + 4].copy_from_slice(&color);
+    }
+
+    fn fill_rect(&mut self, rect: Rect, color: [u8; 4]) {
+        let (min, max) = (self.pixel(rect.min), self.pixel(rect.max));
+        for y in max.y..=min.y {
+            for x in min.x..=max.x {
+                self.set(IVec2::new(x, y), color);
+            }
+        }
+    }
+
+    fn dot(&mut self, position: Vec2, radius: i32, color: [u8; 4]) {
+        let center = self.pixel(position);
+        for y in -radius..=radius {
+            for x in -radius..=radius {
+                if x * x + y * y <= radius * radius {
+                    self.set(center + IVec2::new(x, y), color);
+                }
+            }
+        }
+    }
+}
+
+fn draw_minimap(
+    time: Res<Time>,
+    mut minimap: ResMut<Minimap>,
+    mut images: ResMut<Assets<Image>>,
+    level_bounds: Res<LevelBounds>,
+    density: Res<SwarmDensity>,
+    obstacle_query: Query<(&GlobalTransform, &Collider), With<Obstacal>>,
+    threat_query: Query<
+        (&GlobalTransform, Option<&Species>, Has<Hazard>),
+        (Or<(With<Species>, With<Hazard>)>, Without<Whale>),
+    >,
+    whale_query: Query<(&GlobalTransform, Option<&PlayerId>), With<Whale>>,
+) {
+    if !minimap.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    let Some(image) = images.get_mut(&minimap.image) else {
+        return;
+    };
+
+    let size = minimap_size(&level_bounds);
+    if image.size() != size {
+        image.resize(Extent3d {
+            width: size.x,
+            height: size.y,
+            depth_or_array_layers: 1,
+        });
+    }
+    let mut canvas = Canvas {
+        image,
+        bounds: level_bounds.rect,
+        size: size.as_ivec2(),
+    };
+    for pixel in canvas.image.data.chunks_exact_mut(4) {
+        pixel.copy_from_slice(&WATER_COLOR);
+    }
+
+    // a heatmap of the krill, brightest where the biggest swarm is
+    let busiest = density.counts.iter().copied().max().unwrap_or(0).max(1) as f32;
+    for (index, &count) in density.counts.iter().enumerate() {
+        if count == 0 || density.columns == 0 {
+            continue;
+        }
+        let cell = Vec2::new(
+            (index % density.columns) as f32,
+            (index / density.columns) as f32,
+        );
+        let min = density.origin + cell * density.cell_size;
+        let heat = count as f32 / busiest;
+        let blend = |water: u8, hot: u8| (water as f32 + (hot as f32 - water as f32) * heat) as u8;
+        canvas.fill_rect(
+            Rect::from_corners(min, min + Vec2::splat(density.cell_size - 0.01)),
+            [
+                blend(WATER_COLOR[0], HEAT_COLOR[0]),
+                blend(WATER_COLOR[1], HEAT_COLOR[1]),
+                blend(WATER_COLOR[2], HEAT_COLOR[2]),
+                WATER_COLOR[3],
+            ],
+        );
+    }
+
+    for (transform, collider) in &obstacle_query {
+        if let Some(cuboid) = collider.as_cuboid() {
+            let center = transform.translation().xy();
+            let half_extents = cuboid.half_extents();
+            canvas.fill_rect(
+                Rect::from_center_half_size(center, half_extents),
+                OBSTACLE_COLOR,
+            );
+        }
+    }
+
+    // fish only count when they bite: aggressive ones, or any with a hazard about them
+    for (transform, species, hazard) in &threat_query {
+        let aggressive = species.is_some_and(|species| {
+            let def = species.def();
+            def.aggression > 0.0 || def.contact_damage > 0.0
+        });
+        if hazard || aggressive {
+            canvas.dot(transform.translation().xy(), THREAT_RADIUS, THREAT_COLOR);
+        }
+    }
+
+    for (transform, id) in &whale_query {
+        let color = id.map_or(RIVAL_COLOR, |id| id.color().as_rgba_u8());
+        canvas.dot(transform.translation().xy(), WHALE_RADIUS, color);
+    }
+}
@@ -0,0 +1,166 @@
+use bevy::{
+    prelude::*,
+    utils::{Duration, Instant},
+};
+use bevy_rapier2d::{
+    plugin::RapierContext,
+    prelude::PhysicsSet,
+    render::{DebugRenderContext, RapierDebugRenderPlugin},
+};
+
+const TOGGLE_KEY: KeyCode = KeyCode::F5;
+const FONT_SIZE: f32 = 16.0;
+// clear of the HUD's timer and krill count
+const OVERLAY_TOP: f32 = 56.0;
+// how much each new step moves the averaged step time
+const STEP_SMOOTHING: f32 = 0.1;
+
+/// Collider rendering and a physics stats overlay, toggled together with F5.
+pub struct PhysicsDebugPlugin;
+
+impl Plugin for PhysicsDebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(RapierDebugRenderPlugin::default().disabled())
+            .init_resource::<StepTiming>()
+            .add_systems(Startup, spawn_stats_overlay)
+            .add_systems(
+                PostUpdate,
+                (
+                    start_step_timing
+                        .after(PhysicsSet::SyncBackend)
+                        .before(PhysicsSet::StepSimulation),
+                    finish_step_timing.after(PhysicsSet::StepSimulation),
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    toggle_physics_debug,
+                    update_stats_overlay.run_if(|render: Res<DebugRenderContext>| render.enabled),
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Wall-clock time Rapier spends stepping the simulation.
+#[derive(Resource, Debug, Default)]
+pub struct StepTiming {
+    started: Option<Instant>,
+    pub last: Duration,
+    /// milliseconds, smoothed over recent steps
+    pub average_ms: f32,
+}
+
+#[derive(Component, Debug)]
+struct PhysicsStatsText;
+
+// Centred under the run timer and krill count; the player panels have every corner.
+fn spawn_stats_overlay(mut commands: Commands) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                top: Val::Px(OVERLAY_TOP),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font_size: FONT_SIZE,
+                            color: Color::WHITE,
+                            ..default()
+                        },
+                    ),
+                    background_color: Color::rgba(0.0, 0.0, 0.0, 0.6).into(),
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                PhysicsStatsText,
+            ));
+        });
+}
+
+fn toggle_physics_debug(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut render: ResMut<DebugRenderContext>,
+    mut overlay_query: Query<&mut Visibility, With<PhysicsStatsText>>,
+) {
+    if !keyboard_input.just_pressed(TOGGLE_KEY) {
+        return;
+    }
+    render.enabled = !render.enabled;
+    for mut visibility in &mut overlay_query {
+        *visibility = if render.enabled {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+fn start_step_timing(mut timing: ResMut<StepTiming>) {
+    timing.started = Some(Instant::now());
+}
+
+fn finish_step_timing(mut timing: ResMut<StepTiming>) {
+    let Some(started) = timing.started.take() else {
+        return;
+    };
+    timing.last = started.elapsed();
+    let last_ms = timing.last.as_secs_f32() * 1000.0;
+    timing.average_ms += (last_ms - timing.average_ms) * STEP_SMOOTHING;
+}
+
+fn update_stats_overlay(
+    rapier_context: Res<RapierContext>,
+    timing: Res<StepTiming>,
+    mut overlay_query: Query<&mut Text, With<PhysicsStatsText>>,
+) {
+    let Ok(mut text) = overlay_query.get_single_mut() else {
+        return;
+    };
+
+    let (mut dynamic, mut kinematic, mut fixed, mut sleeping) = (0, 0, 0, 0);
+    for (_, body) in rapier_context.bodies.iter() {
+        if body.is_dynamic() {
+            dynamic += 1;
+        } else if body.is_kinematic() {
+            kinematic += 1;
+        } else if body.is_fixed() {
+            fixed += 1;
+        }
+        if body.is_sleeping() {
+            sleeping += 1;
+        }
+    }
+    let narrow_phase = &rapier_context.narrow_phase;
+    let contact_pairs = narrow_phase.contact_pairs().count();
+    let touching_pairs = narrow_phase
+        .contact_pairs()
+        .filter(|pair| pair.has_any_active_contact)
+        .count();
+    let intersecting_pairs = narrow_phase
+        .intersection_pairs()
+        .filter(|(_, _, intersecting)| *intersecting)
+        .count();
+
+    text.sections[0].value = format!(
+        "bodies: {} ({dynamic} dynamic, {kinematic} kinematic, {fixed} fixed, {sleeping} asleep)\n\
+         colliders: {}\n\
+         contact pairs: {touching_pairs} touching of {contact_pairs}\n\
+         sensor intersections: {intersecting_pairs}\n\
+         step: {:.2} ms (avg {:.2} ms)",
+        rapier_context.bodies.len(),
+        rapier_context.colliders.len(),
+        timing.last.as_secs_f32() * 1000.0,
+        timing.average_ms,
+    );
+}
@@ -0,0 +1,99 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::{
+    NoUserData, PhysicsSet, RapierConfiguration, RapierPhysicsPlugin, Vect,
+};
+
+use crate::in_gameplay;
+
+#[cfg(feature = "debug")]
+pub mod debug;
+
+pub struct PhysicsPlugin;
+
+impl Plugin for PhysicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(RapierPhysicsPlugin::<NoUserData>::default())
+            .insert_resource(RapierConfiguration {
+                gravity: Vect::ZERO,
+                ..Default::default()
+            })
+            .register_type::<KinematicVelocity>()
+            .register_type::<KinematicAcceleration>()
+            .register_type::<KinematicDrag>()
+            // kinematic entities are moved before Rapier syncs transforms, so sensors and
+            // colliders on them see this frame's position
+            .configure_sets(
+                PostUpdate,
+                KinematicSet
+                    .before(PhysicsSet::SyncBackend)
+                    .run_if(in_gameplay),
+            )
+            .add_systems(
+                PostUpdate,
+                (kinematic_acceleration, kinematic_drag, kinematic_velocity)
+                    .chain()
+                    .in_set(KinematicSet),
+            );
+    }
+}
+
+/// Integration of the kinematic motion components, for entities Rapier does not simulate.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct KinematicSet;
+
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct KinematicVelocity {
+    pub linear: Vec2,
+    /// radians per second, counter-clockwise
+    pub angular: f32,
+}
+
+impl KinematicVelocity {
+    pub fn linear(linear: Vec2) -> Self {
+        Self {
+            linear,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct KinematicAcceleration {
+    pub linear: Vec2,
+    pub angular: f32,
+}
+
+/// Exponential decay rate per second: velocity is scaled by `exp(-drag * dt)` each frame, so a
+/// drag of 1.0 loses about 63% of it per second.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct KinematicDrag {
+    pub linear: f32,
+    pub angular: f32,
+}
+
+fn kinematic_acceleration(
+    time: Res<Time>,
+    mut query: Query<(&KinematicAcceleration, &mut KinematicVelocity)>,
+) {
+    for (acceleration, mut velocity) in query.iter_mut() {
+        velocity.linear += acceleration.linear * time.delta_seconds();
+        velocity.angular += acceleration.angular * time.delta_seconds();
+    }
+}
+
+fn kinematic_drag(time: Res<Time>, mut query: Query<(&KinematicDrag, &mut KinematicVelocity)>) {
+    for (drag, mut velocity) in query.iter_mut() {
+        velocity.linear *= (-drag.linear * time.delta_seconds()).exp();
+        velocity.angular *= (-drag.angular * time.delta_seconds()).exp();
+    }
+}
+
+fn kinematic_velocity(time: Res<Time>, mut query: Query<(&KinematicVelocity, &mut Transform)>) {
+    for (velocity, mut transform) in query.iter_mut() {
+        transform.translation += (velocity.linear * time.delta_seconds()).extend(0.0);
+        transform.rotate_z(velocity.angular * time.delta_seconds());
+    }
+}
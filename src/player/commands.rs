@@ -0,0 +1,74 @@
+use bevy::prelude::*;
+use bevy_rapier2d::dynamics::Velocity;
+
+use super::{Player, PlayerId};
+use crate::{
+    console::{parse_arg, require_run, ConsoleCommand},
+    health::GodMode,
+};
+
+pub struct GodModeCommand;
+
+impl ConsoleCommand for GodModeCommand {
+    fn name(&self) -> &'static str {
+        "godmode"
+    }
+
+    fn run(&self, _args: &[&str], world: &mut World) -> Result<String, String> {
+        require_run(world)?;
+        let players: Vec<(Entity, bool)> = world
+            .query_filtered::<(Entity, Has<GodMode>), With<Player>>()
+            .iter(world)
+            .collect();
+        // a player who joined since the last toggle is brought in line with the first
+        let Some(&(_, was_on)) = players.first() else {
+            return Err("no players".to_string());
+        };
+        for (player, _) in players {
+            if was_on {
+                world.entity_mut(player).remove::<GodMode>();
+            } else {
+                world.entity_mut(player).insert(GodMode);
+            }
+        }
+        Ok(format!("godmode {}", if was_on { "off" } else { "on" }))
+    }
+}
+
+pub struct TeleportCommand;
+
+impl ConsoleCommand for TeleportCommand {
+    fn name(&self) -> &'static str {
+        "teleport"
+    }
+
+    fn usage(&self) -> &'static str {
+        "<x> <y> [player]"
+    }
+
+    fn run(&self, args: &[&str], world: &mut World) -> Result<String, String> {
+        require_run(world)?;
+        let position = Vec2::new(parse_arg(args, 0, "x")?, parse_arg(args, 1, "y")?);
+        // players are numbered from 1 on screen
+        let number: usize = if args.len() > 2 {
+            parse_arg(args, 2, "player")?
+        } else {
+            1
+        };
+        let player = world
+            .query_filtered::<(Entity, &PlayerId), With<Player>>()
+            .iter(world)
+            .find(|(_, id)| id.0 + 1 == number)
+            .map(|(entity, _)| entity)
+            .ok_or_else(|| format!("no player {number}"))?;
+
+        let mut player = world.entity_mut(player);
+        if let Some(mut transform) = player.get_mut::<Transform>() {
+            transform.translation = position.extend(transform.translation.z);
+        }
+        if let Some(mut velocity) = player.get_mut::<Velocity>() {
+            *velocity = Velocity::zero();
+        }
+        Ok(format!("player {number} moved to {position}"))
+    }
+}
@@ -0,0 +1,174 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+use serde::{Deserialize, Serialize};
+
+use crate::camera::MainCamera;
+
+const STICK_DEADZONE: f32 = 0.2;
+
+/// Which device a local player is driving their whale with.
+#[derive(Component, Clone, Copy, Eq, PartialEq, Debug)]
+pub enum InputScheme {
+    /// WASD, aiming with the mouse
+    KeyboardLeft,
+    /// arrow keys
+    KeyboardRight,
+    Gamepad(Gamepad),
+}
+
+/// Keys for one half of a shared keyboard.
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Reflect, Serialize, Deserialize)]
+pub struct KeyBindings {
+    pub up: KeyCode,
+    pub down: KeyCode,
+    pub left: KeyCode,
+    pub right: KeyCode,
+    pub boost: KeyCode,
+    pub gulp: KeyCode,
+    pub sonar: KeyCode,
+    /// also joins the game
+    pub fire: KeyCode,
+}
+
+#[derive(Resource, Clone, Copy, Eq, PartialEq, Debug, Reflect, Serialize, Deserialize)]
+#[reflect(Resource)]
+pub struct InputBindings {
+    pub keyboard_left: KeyBindings,
+    pub keyboard_right: KeyBindings,
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        Self {
+            keyboard_left: KeyBindings {
+                up: KeyCode::W,
+                down: KeyCode::S,
+                left: KeyCode::A,
+                right: KeyCode::D,
+                boost: KeyCode::ShiftLeft,
+                gulp: KeyCode::E,
+                sonar: KeyCode::F,
+                fire: KeyCode::Space,
+            },
+            keyboard_right: KeyBindings {
+                up: KeyCode::Up,
+                down: KeyCode::Down,
+                left: KeyCode::Left,
+                right: KeyCode::Right,
+                boost: KeyCode::ShiftRight,
+                gulp: KeyCode::Period,
+                sonar: KeyCode::Comma,
+                fire: KeyCode::ControlRight,
+            },
+        }
+    }
+}
+
+impl InputBindings {
+    pub fn keys(&self, scheme: InputScheme) -> Option<&KeyBindings> {
+        match scheme {
+            InputScheme::KeyboardLeft => Some(&self.keyboard_left),
+            InputScheme::KeyboardRight => Some(&self.keyboard_right),
+            InputScheme::Gamepad(_) => None,
+        }
+    }
+}
+
+/// What a player asked their whale to do this frame, whatever device they use.
+#[derive(Component, Clone, Copy, PartialEq, Debug, Default)]
+pub struct PlayerActions {
+    pub movement: Vec2,
+    /// world-space direction to aim in, when the device has a separate aim
+    pub aim: Option<Vec2>,
+    pub boost: bool,
+    pub gulp: bool,
+    pub sonar: bool,
+    pub fire: bool,
+}
+
+/// Whether `scheme` pressed its join button this frame.
+pub fn join_pressed(
+    scheme: InputScheme,
+    bindings: &InputBindings,
+    keyboard: &Input<KeyCode>,
+    buttons: &Input<GamepadButton>,
+) -> bool {
+    match scheme {
+        InputScheme::Gamepad(gamepad) => buttons.any_just_pressed([
+            GamepadButton::new(gamepad, GamepadButtonType::South),
+            GamepadButton::new(gamepad, GamepadButtonType::Start),
+        ]),
+        _ => bindings
+            .keys(scheme)
+            .is_some_and(|keys| keyboard.just_pressed(keys.fire)),
+    }
+}
+
+pub fn read_player_input(
+    bindings: Res<InputBindings>,
+    keyboard: Res<Input<KeyCode>>,
+    buttons: Res<Input<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    mut player_query: Query<(&InputScheme, &Transform, &mut PlayerActions)>,
+) {
+    let cursor = window_query
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+        .zip(camera_query.get_single().ok())
+        .and_then(|(cursor, (camera, camera_transform))| {
+            camera.viewport_to_world_2d(camera_transform, cursor)
+        });
+
+    for (scheme, transform, mut actions) in &mut player_query {
+        *actions = match *scheme {
+            InputScheme::Gamepad(gamepad) => {
+                let stick = |x, y| {
+                    Vec2::new(
+                        axes.get(GamepadAxis::new(gamepad, x)).unwrap_or(0.0),
+                        axes.get(GamepadAxis::new(gamepad, y)).unwrap_or(0.0),
+                    )
+                };
+                let button = |button_type| GamepadButton::new(gamepad, button_type);
+                let movement = stick(GamepadAxisType::LeftStickX, GamepadAxisType::LeftStickY);
+                let aim = stick(GamepadAxisType::RightStickX, GamepadAxisType::RightStickY);
+
+                PlayerActions {
+                    movement: if movement.length() > STICK_DEADZONE {
+                        movement.clamp_length_max(1.0)
+                    } else {
+                        Vec2::ZERO
+                    },
+                    aim: (aim.length() > STICK_DEADZONE).then(|| aim.normalize()),
+                    boost: buttons.pressed(button(GamepadButtonType::LeftTrigger2)),
+                    gulp: buttons.pressed(button(GamepadButtonType::South)),
+                    sonar: buttons.just_pressed(button(GamepadButtonType::West)),
+                    fire: buttons.just_pressed(button(GamepadButtonType::RightTrigger2)),
+                }
+            }
+            keyboard_scheme => {
+                let Some(keys) = bindings.keys(keyboard_scheme) else {
+                    continue;
+                };
+                let axis = |negative, positive| {
+                    keyboard.pressed(positive) as i8 as f32
+                        - keyboard.pressed(negative) as i8 as f32
+                };
+
+                PlayerActions {
+                    movement: Vec2::new(axis(keys.left, keys.right), axis(keys.down, keys.up))
+                        .normalize_or_zero(),
+                    // only one player can own the mouse
+                    aim: cursor
+                        .filter(|_| keyboard_scheme == InputScheme::KeyboardLeft)
+                        .and_then(|cursor| (cursor - transform.translation.xy()).try_normalize()),
+                    boost: keyboard.pressed(keys.boost),
+                    gulp: keyboard.pressed(keys.gulp),
+                    sonar: keyboard.just_pressed(keys.sonar),
+                    fire: keyboard.just_pressed(keys.fire),
+                }
+            }
+        };
+    }
+}
@@ -0,0 +1,748 @@
+// use bevy::sprite::collide_aabb::Collision;
+// use bevy::{ecs::event::event_update_condition, prelude::*};
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use self::input::{join_pressed, read_player_input, InputBindings, InputScheme, PlayerActions};
+#[cfg(feature = "debug")]
+use crate::console::AddConsoleCommand;
+use crate::{
+    assets::ImageAssets,
+    despawn::{DespawnOutsideBounds, Lifetime, RunScoped},
+    growth::Growth,
+    health::{DamageEvent, DeathEvent, Health},
+    in_gameplay,
+    krill::systems::{EatenKrill, Krill},
+    physics::KinematicVelocity,
+    sonar::Sonar,
+    GameState, START_RUN,
+};
+
+#[cfg(feature = "debug")]
+mod commands;
+pub mod input;
+
+pub const MAX_PLAYERS: usize = 4;
+const PLAYER_SPEED: f32 = 50.0;
+const PLAYER_SCALE: f32 = 0.50;
+const DAMPING: f32 = 3.0;
+const LASER_SPEED: f32 = 200.0;
+const LASER_LIFETIME: f32 = 3.0;
+const LASER_BOUNDS_MARGIN: f32 = 20.0;
+const LASER_DAMAGE: f32 = 10.0;
+const ROTATION_SPEED: f32 = 10.0;
+const STAMINA_MAX: f32 = 100.0;
+const STAMINA_REGEN: f32 = 20.0;
+// stamina spent per second of boosting
+const BOOST_COST: f32 = 60.0;
+// boosting needs at least this much stamina to start, so it can't be feathered on an empty bar
+const BOOST_MIN_STAMINA: f32 = 15.0;
+const BOOST_SPEED_MULTIPLIER: f32 = 2.5;
+const BOOST_TURN_MULTIPLIER: f32 = 0.3;
+const PLAYER_HEALTH: f32 = 100.0;
+const PLAYER_INVULNERABILITY: f32 = 1.5;
+const DEATH_DURATION: f32 = 2.0;
+const DEATH_SINK_SPEED: f32 = 15.0;
+// gulp range is in world units at the whale's starting size, and grows with it
+const GULP_RANGE: f32 = 30.0;
+const GULP_HALF_ANGLE: f32 = 0.6;
+const GULP_STRENGTH: f32 = 400.0;
+const GULP_MAX_HOLD: f32 = 1.5;
+const GULP_COOLDOWN: f32 = 2.0;
+const GULP_MOVE_MULTIPLIER: f32 = 0.4;
+// offsets are in the whale sprite's local space, before PLAYER_SCALE is applied
+const MOUTH_OFFSET: f32 = 12.0;
+const MOUTH_RADIUS: f32 = 4.0;
+const LASER_SPAWN_OFFSET: f32 = 20.0;
+// whales joining later spawn spread out around the middle of the level
+const SPAWN_SPACING: f32 = 30.0;
+const PLAYER_COLORS: [Color; MAX_PLAYERS] = [
+    Color::WHITE,
+    Color::rgb(1.0, 0.6, 0.6),
+    Color::rgb(0.6, 1.0, 0.6),
+    Color::rgb(1.0, 1.0, 0.5),
+];
+
+/// Any whale, whether a local player or an AI drives it through its `PlayerActions`.
+#[derive(Clone, Eq, PartialEq, Debug, Default, Component)]
+pub struct Whale;
+
+/// A whale controlled by a local player.
+#[derive(Clone, Eq, PartialEq, Debug, Default, Component)]
+pub struct Player;
+
+/// Which local player, from 0, a whale belongs to.
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default, Component, Reflect)]
+#[reflect(Component)]
+pub struct PlayerId(pub usize);
+
+impl PlayerId {
+    pub fn color(&self) -> Color {
+        PLAYER_COLORS[self.0 % MAX_PLAYERS]
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default, Component, Reflect)]
+#[reflect(Component)]
+pub struct Score(pub usize);
+
+/// Fired by `owner`, who is credited with whatever it kills.
+#[derive(Component)]
+pub struct Laser {
+    pub owner: Entity,
+}
+
+/// Sensor at the whale's nose, the only part of the whale that eats krill.
+#[derive(Clone, Eq, PartialEq, Debug, Default, Component)]
+pub struct Mouth;
+
+/// The one direction the whale's nose points in. Movement turns it, and the sprite,
+/// mouth and (by default) weapons all follow it.
+#[derive(Clone, Copy, PartialEq, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct Facing(pub Vec2);
+
+impl Default for Facing {
+    fn default() -> Self {
+        Self(Vec2::X)
+    }
+}
+
+impl Facing {
+    pub fn angle(&self) -> f32 {
+        self.0.y.atan2(self.0.x)
+    }
+
+    /// Turns towards `target` by `amount` of the remaining angle, taking the short way round.
+    pub fn turn_towards(&mut self, target: Vec2, amount: f32) {
+        let remaining = self.0.angle_between(target);
+        if remaining.is_nan() {
+            return;
+        }
+        self.0 = Vec2::from_angle(self.angle() + remaining * amount.clamp(0.0, 1.0));
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct Stamina {
+    pub current: f32,
+    pub max: f32,
+}
+
+impl Default for Stamina {
+    fn default() -> Self {
+        Self {
+            current: STAMINA_MAX,
+            max: STAMINA_MAX,
+        }
+    }
+}
+
+impl Stamina {
+    pub fn fraction(&self) -> f32 {
+        self.current / self.max
+    }
+}
+
+/// A short burst of speed paid for with `Stamina`, at the cost of turning.
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default, Component, Reflect)]
+#[reflect(Component)]
+pub struct Boost {
+    pub active: bool,
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct KrillEatenEvent {
+    /// the whale whose mouth swallowed the krill
+    pub eater: Entity,
+    /// where the krill was when it went down
+    pub position: Vec2,
+}
+
+/// Hold-to-gulp filter feeding: while active, krill in a cone in front of the mouth are sucked in.
+#[derive(Clone, PartialEq, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct Gulp {
+    pub active: bool,
+    pub range: f32,
+    /// radians either side of `Facing`
+    pub half_angle: f32,
+    /// acceleration applied to krill inside the cone
+    pub strength: f32,
+    pub hold: Timer,
+    pub cooldown: Timer,
+}
+
+impl Default for Gulp {
+    fn default() -> Self {
+        let mut cooldown = Timer::from_seconds(GULP_COOLDOWN, TimerMode::Once);
+        // ready from the start
+        cooldown.tick(cooldown.duration());
+        Self {
+            active: false,
+            range: GULP_RANGE,
+            half_angle: GULP_HALF_ANGLE,
+            strength: GULP_STRENGTH,
+            hold: Timer::from_seconds(GULP_MAX_HOLD, TimerMode::Once),
+            cooldown,
+        }
+    }
+}
+
+impl Gulp {
+    /// Whether `point` is inside the suction cone of a mouth at `mouth` facing `facing`.
+    pub fn reaches(&self, mouth: Vec2, facing: Vec2, size: f32, point: Vec2) -> bool {
+        let offset = point - mouth;
+        offset.length() < self.range * size && facing.angle_between(offset).abs() < self.half_angle
+    }
+}
+
+/// Plays the death animation, then ends the run once no whale is left.
+#[derive(Component, Debug, Deref, DerefMut)]
+pub struct Dying(pub Timer);
+
+/// Direction weapons fire in. Mirrors `Facing` unless twin-stick aiming is on.
+#[derive(Clone, Copy, PartialEq, Debug, Default, Component, Reflect)]
+#[reflect(Component)]
+pub struct Aim(pub Vec2);
+
+#[derive(Resource, Clone, Copy, Eq, PartialEq, Debug, Default, Reflect)]
+#[reflect(Resource)]
+pub enum AimMode {
+    #[default]
+    Facing,
+    /// aim with the mouse cursor or a gamepad's right stick, independent of movement
+    TwinStick,
+}
+
+/// Everything a whale needs to swim, eat and fight, whoever is controlling it.
+#[derive(Bundle)]
+pub struct WhaleBundle {
+    whale: Whale,
+    actions: PlayerActions,
+    facing: Facing,
+    aim: Aim,
+    stamina: Stamina,
+    boost: Boost,
+    health: Health,
+    growth: Growth,
+    gulp: Gulp,
+    sonar: Sonar,
+    sprite: SpriteSheetBundle,
+    collider: Collider,
+    rigid_body: RigidBody,
+    damping: Damping,
+    run_scoped: RunScoped,
+}
+
+impl WhaleBundle {
+    pub fn new(image_assets: &ImageAssets, position: Vec2, color: Color) -> Self {
+        Self {
+            whale: Whale,
+            actions: PlayerActions::default(),
+            facing: Facing::default(),
+            aim: Aim(Facing::default().0),
+            stamina: Stamina::default(),
+            boost: Boost::default(),
+            health: Health::new(PLAYER_HEALTH, PLAYER_INVULNERABILITY),
+            growth: Growth::new(PLAYER_SCALE),
+            gulp: Gulp::default(),
+            sonar: Sonar::default(),
+            sprite: SpriteSheetBundle {
+                transform: Transform {
+                    translation: position.extend(0.0),
+                    scale: Vec3::new(PLAYER_SCALE, PLAYER_SCALE, 1.0),
+                    ..Default::default()
+                },
+                sprite: TextureAtlasSprite {
+                    color,
+                    ..TextureAtlasSprite::new(0)
+                },
+                texture_atlas: image_assets.whale.clone(),
+                ..Default::default()
+            },
+            collider: Collider::cuboid(2.0, 2.0),
+            rigid_body: RigidBody::Dynamic,
+            //This is the specific area, where you can adjust the bouncing off of walls
+            // You can add and play with much more here in regards to physics
+            damping: Damping {
+                linear_damping: DAMPING,
+                angular_damping: DAMPING,
+            },
+            run_scoped: RunScoped,
+        }
+    }
+}
+
+/// Players who died this run, and the input they played with, so they can't join again.
+#[derive(Resource, Debug, Default)]
+pub struct FallenPlayers(pub Vec<(PlayerId, InputScheme)>);
+
+#[derive(Bundle)]
+pub struct PlayerBundle {
+    player: Player,
+    id: PlayerId,
+    input_scheme: InputScheme,
+    score: Score,
+    whale: WhaleBundle,
+}
+
+pub struct PlayerPlugin;
+impl Plugin for PlayerPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Facing>()
+            .register_type::<Aim>()
+            .register_type::<AimMode>()
+            .register_type::<Stamina>()
+            .register_type::<Boost>()
+            .register_type::<Gulp>()
+            .register_type::<PlayerId>()
+            .register_type::<Score>()
+            .register_type::<InputBindings>()
+            .init_resource::<AimMode>()
+            .init_resource::<InputBindings>()
+            .init_resource::<FallenPlayers>()
+            .add_event::<KrillEatenEvent>()
+            .add_systems(START_RUN, spawn_player)
+            .add_systems(
+                Update,
+                (
+                    join_players,
+                    (
+                        read_player_input,
+                        player_boost,
+                        player_gulp,
+                        player_movement,
+                        face_sprite,
+                        toggle_aim_mode,
+                        update_aim,
+                        spawn_laser,
+                    )
+                        .chain(),
+                    laser_hits,
+                    eat_krill,
+                    draw_gulp,
+                    (start_dying, animate_death).chain(),
+                )
+                    .run_if(in_gameplay),
+            );
+
+        #[cfg(feature = "debug")]
+        app.add_console_command(commands::GodModeCommand)
+            .add_console_command(commands::TeleportCommand);
+    }
+}
+
+// This function is a very basic movement system that does not incorporate collisions and physics that can be found in Rapier
+
+//This function is likely to evolve as physics become decided
+pub fn player_movement(
+    mut query: Query<
+        (
+            &PlayerActions,
+            &mut Transform,
+            &mut Facing,
+            &Boost,
+            &Gulp,
+            &Growth,
+        ),
+        (With<Whale>, Without<Dying>),
+    >,
+    time: Res<Time>,
+) {
+    for (actions, mut transform, mut facing, boost, gulp, growth) in &mut query {
+        let Some(target_direction) = actions.movement.try_normalize() else {
+            continue;
+        };
+        // analog sticks can ask for less than full speed
+        let throttle = actions.movement.length().min(1.0);
+
+        let (speed, rotation_speed) = if boost.active {
+            (
+                PLAYER_SPEED * BOOST_SPEED_MULTIPLIER,
+                ROTATION_SPEED * BOOST_TURN_MULTIPLIER,
+            )
+        } else {
+            (PLAYER_SPEED, ROTATION_SPEED)
+        };
+        let (speed, rotation_speed) = (speed * growth.speed, rotation_speed * growth.turn_rate);
+        let speed = if gulp.active {
+            speed * GULP_MOVE_MULTIPLIER
+        } else {
+            speed
+        };
+
+        transform.translation +=
+            (target_direction * throttle * speed * time.delta_seconds()).extend(0.0);
+        facing.turn_towards(target_direction, rotation_speed * time.delta_seconds());
+    }
+}
+
+pub fn player_gulp(
+    mut query: Query<(&PlayerActions, &mut Gulp), (With<Whale>, Without<Dying>)>,
+    time: Res<Time>,
+) {
+    for (actions, mut gulp) in &mut query {
+        let held = actions.gulp;
+        if !gulp.cooldown.tick(time.delta()).finished() {
+            continue;
+        }
+
+        if held && !gulp.hold.tick(time.delta()).finished() {
+            gulp.active = true;
+        } else if gulp.active {
+            // the mouth snaps shut on release or once the whale runs out of breath
+            gulp.active = false;
+            gulp.hold.reset();
+            gulp.cooldown.reset();
+        } else if !held {
+            gulp.hold.reset();
+        }
+    }
+}
+
+fn draw_gulp(
+    mut gizmos: Gizmos,
+    mouth_query: Query<(&GlobalTransform, &Parent), With<Mouth>>,
+    whale_query: Query<(&Gulp, &Facing, &Growth), Without<Dying>>,
+) {
+    for (mouth_transform, parent) in &mouth_query {
+        let Ok((gulp, facing, growth)) = whale_query.get(parent.get()) else {
+            continue;
+        };
+        if !gulp.active {
+            continue;
+        }
+        // arc_2d measures its direction clockwise from +y
+        gizmos.arc_2d(
+            mouth_transform.translation().xy(),
+            FRAC_PI_2 - facing.angle(),
+            gulp.half_angle * 2.0,
+            gulp.range * growth.size,
+            Color::rgba(0.6, 0.9, 1.0, 0.5),
+        );
+    }
+}
+
+pub fn player_boost(
+    mut query: Query<(&PlayerActions, &mut Stamina, &mut Boost), (With<Whale>, Without<Dying>)>,
+    time: Res<Time>,
+) {
+    for (actions, mut stamina, mut boost) in &mut query {
+        let held = actions.boost;
+        let can_boost = if boost.active {
+            stamina.current > 0.0
+        } else {
+            stamina.current >= BOOST_MIN_STAMINA
+        };
+        boost.active = held && can_boost;
+
+        let change = if boost.active {
+            -BOOST_COST
+        } else {
+            STAMINA_REGEN
+        };
+        stamina.current = (stamina.current + change * time.delta_seconds()).clamp(0.0, stamina.max);
+    }
+}
+
+// The whale sheet is drawn nose-right, so the sprite is flipped rather than left upside down
+// whenever the whale faces left.
+fn face_sprite(
+    mut query: Query<(&Facing, &mut Transform, &mut TextureAtlasSprite), Changed<Facing>>,
+) {
+    for (facing, mut transform, mut sprite) in &mut query {
+        transform.rotation = Quat::from_rotation_z(facing.angle());
+        sprite.flip_y = facing.0.x < 0.0;
+    }
+}
+
+fn toggle_aim_mode(input: Res<Input<KeyCode>>, mut aim_mode: ResMut<AimMode>) {
+    if input.just_pressed(KeyCode::Tab) {
+        *aim_mode = match *aim_mode {
+            AimMode::Facing => AimMode::TwinStick,
+            AimMode::TwinStick => AimMode::Facing,
+        };
+        info!("aim mode: {:?}", *aim_mode);
+    }
+}
+
+fn update_aim(
+    aim_mode: Res<AimMode>,
+    mut player_query: Query<(&PlayerActions, &Facing, &mut Aim), With<Whale>>,
+) {
+    for (actions, facing, mut aim) in &mut player_query {
+        let twin_stick_aim = match *aim_mode {
+            AimMode::Facing => None,
+            AimMode::TwinStick => actions.aim,
+        };
+        aim.0 = twin_stick_aim.unwrap_or(facing.0);
+    }
+}
+
+// The first player is always on the left of the keyboard, everyone else joins in
+pub fn spawn_player(
+    mut commands: Commands,
+    image_assets: Res<ImageAssets>,
+    mut fallen: ResMut<FallenPlayers>,
+) {
+    fallen.0.clear();
+    spawn_player_whale(
+        &mut commands,
+        &image_assets,
+        PlayerId(0),
+        InputScheme::KeyboardLeft,
+    );
+}
+
+fn join_players(
+    mut commands: Commands,
+    image_assets: Res<ImageAssets>,
+    bindings: Res<InputBindings>,
+    keyboard: Res<Input<KeyCode>>,
+    buttons: Res<Input<GamepadButton>>,
+    gamepads: Res<Gamepads>,
+    player_query: Query<(&PlayerId, &InputScheme)>,
+    fallen: Res<FallenPlayers>,
+) {
+    let schemes = [InputScheme::KeyboardLeft, InputScheme::KeyboardRight]
+        .into_iter()
+        .chain(gamepads.iter().map(InputScheme::Gamepad));
+
+    // a player who died stays out until the next run, and keeps their number
+    let mut taken: Vec<_> = player_query
+        .iter()
+        .map(|(id, scheme)| (*id, *scheme))
+        .chain(fallen.0.iter().copied())
+        .collect();
+    for scheme in schemes {
+        if taken
+            .iter()
+            .any(|(_, taken_scheme)| *taken_scheme == scheme)
+            || !join_pressed(scheme, &bindings, &keyboard, &buttons)
+        {
+            continue;
+        }
+        let Some(id) = (0..MAX_PLAYERS)
+            .map(PlayerId)
+            .find(|id| taken.iter().all(|(taken_id, _)| taken_id != id))
+        else {
+            return;
+        };
+        info!("player {} joined with {:?}", id.0 + 1, scheme);
+        spawn_player_whale(&mut commands, &image_assets, id, scheme);
+        taken.push((id, scheme));
+    }
+}
+
+fn spawn_player_whale(
+    commands: &mut Commands,
+    image_assets: &ImageAssets,
+    id: PlayerId,
+    input_scheme: InputScheme,
+) {
+    let spawn_offset = match id.0 {
+        0 => Vec2::ZERO,
+        n => {
+            Vec2::from_angle(n as f32 * std::f32::consts::TAU / (MAX_PLAYERS - 1) as f32)
+                * SPAWN_SPACING
+        }
+    };
+    spawn_whale(
+        commands,
+        (
+            PlayerBundle {
+                player: Player,
+                id,
+                input_scheme,
+                score: Score::default(),
+                whale: WhaleBundle::new(image_assets, spawn_offset, id.color()),
+            },
+            Name::new(format!("Player {}", id.0 + 1)),
+        ),
+    );
+}
+
+/// Spawns a whale from `bundle`, which should include a `WhaleBundle`, along with its mouth.
+pub fn spawn_whale(commands: &mut Commands, bundle: impl Bundle) -> Entity {
+    commands
+        .spawn(bundle)
+        .with_children(|parent| {
+            parent.spawn((
+                Mouth,
+                Collider::ball(MOUTH_RADIUS),
+                Sensor,
+                ActiveEvents::COLLISION_EVENTS,
+                TransformBundle::from_transform(Transform::from_xyz(MOUTH_OFFSET, 0.0, 0.0)),
+            ));
+        })
+        .id()
+}
+
+fn start_dying(
+    mut commands: Commands,
+    mut death_events: EventReader<DeathEvent>,
+    mut player_query: Query<&mut Boost, With<Whale>>,
+) {
+    for event in death_events.read() {
+        if let Ok(mut boost) = player_query.get_mut(event.entity) {
+            boost.active = false;
+            commands
+                .entity(event.entity)
+                .insert(Dying(Timer::from_seconds(DEATH_DURATION, TimerMode::Once)));
+        }
+    }
+}
+
+// The whale rolls belly-up, fades and sinks, then leaves the game; the run is over once
+// every player's whale has.
+fn animate_death(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<
+        (
+            Entity,
+            &mut Dying,
+            &mut Transform,
+            &mut TextureAtlasSprite,
+            Option<(&PlayerId, &InputScheme)>,
+        ),
+        With<Whale>,
+    >,
+    player_query: Query<(), With<Player>>,
+    mut fallen: ResMut<FallenPlayers>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let mut remaining_players = player_query.iter().len();
+    for (entity, mut dying, mut transform, mut sprite, player) in &mut query {
+        dying.tick(time.delta());
+        let progress = dying.percent();
+
+        transform.rotation = transform
+            .rotation
+            .slerp(Quat::from_rotation_x(std::f32::consts::PI), progress);
+        transform.translation.y -= DEATH_SINK_SPEED * time.delta_seconds();
+        sprite.color.set_a(1.0 - progress);
+
+        if dying.just_finished() {
+            if let Some((id, scheme)) = player {
+                remaining_players -= 1;
+                fallen.0.push((*id, *scheme));
+            }
+            if player.is_some() && remaining_players == 0 {
+                next_state.set(GameState::GameOver);
+            } else {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+    }
+}
+
+fn eat_krill(
+    mut commands: Commands,
+    mut events: EventReader<CollisionEvent>,
+    mouth_query: Query<&Parent, With<Mouth>>,
+    dying_query: Query<(), With<Dying>>,
+    krill_query: Query<&Transform, With<Krill>>,
+    mut eaten_krill: ResMut<EatenKrill>,
+    mut krill_eaten_events: EventWriter<KrillEatenEvent>,
+) {
+    for event in events.read() {
+        match event {
+            CollisionEvent::Started(a, b, _) => {
+                let (mouth, krill) = if mouth_query.contains(*a) {
+                    (*a, *b)
+                } else if mouth_query.contains(*b) {
+                    (*b, *a)
+                } else {
+                    continue;
+                };
+                if let (Ok(whale), Ok(krill_transform)) =
+                    (mouth_query.get(mouth), krill_query.get(krill))
+                {
+                    // a sinking whale's mouth is still a sensor, but it has stopped feeding
+                    if dying_query.contains(whale.get()) || !eaten_krill.claim(krill) {
+                        continue;
+                    }
+                    krill_eaten_events.send(KrillEatenEvent {
+                        eater: whale.get(),
+                        position: krill_transform.translation.xy(),
+                    });
+                    commands.entity(krill).despawn();
+                }
+            }
+            CollisionEvent::Stopped(_a, _b, _) => {}
+        }
+    }
+}
+
+pub fn spawn_laser(
+    mut commands: Commands,
+    query: Query<
+        (Entity, &PlayerActions, &Transform, &Aim, &Growth),
+        (With<Whale>, Without<Dying>),
+    >,
+) {
+    for (whale, actions, player_transform, aim, growth) in &query {
+        if actions.fire {
+            let direction = aim.0.extend(0.0);
+            commands
+                .spawn(SpriteBundle {
+                    sprite: Sprite {
+                        color: Color::RED,
+                        custom_size: Some(Vec2::new(5.0, 15.0)),
+                        ..default()
+                    },
+                    // the laser sprite is long along its y axis, and leaves from outside the
+                    // whale however big it has grown
+                    transform: Transform {
+                        translation: player_transform.translation
+                            + direction * LASER_SPAWN_OFFSET * growth.size,
+                        rotation: Quat::from_rotation_z(aim.0.y.atan2(aim.0.x) - FRAC_PI_2),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .insert(Laser { owner: whale })
+                .insert(KinematicVelocity::linear(aim.0 * LASER_SPEED))
+                .insert(Collider::cuboid(2.5, 7.5))
+                .insert(Sensor)
+                .insert(RigidBody::KinematicPositionBased)
+                // the boss is kinematic too, and kinematic pairs are skipped by default
+                .insert(ActiveCollisionTypes::all())
+                .insert(Lifetime::from_seconds(LASER_LIFETIME))
+                .insert(RunScoped)
+                .insert(DespawnOutsideBounds {
+                    margin: LASER_BOUNDS_MARGIN,
+                });
+        }
+    }
+}
+
+// Lasers hurt anything with health that isn't a whale, and are spent on the first hit.
+fn laser_hits(
+    mut commands: Commands,
+    rapier_context: Res<RapierContext>,
+    laser_query: Query<(Entity, &Laser)>,
+    target_query: Query<(), (With<Health>, Without<Whale>)>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    for (laser, Laser { owner }) in &laser_query {
+        let hit = rapier_context
+            .intersections_with(laser)
+            .filter(|(_, _, intersecting)| *intersecting)
+            .map(|(a, b, _)| if a == laser { b } else { a })
+            .find(|other| target_query.contains(*other));
+        if let Some(target) = hit {
+            damage_events.send(DamageEvent {
+                target,
+                amount: LASER_DAMAGE,
+                source: Some(*owner),
+            });
+            commands.entity(laser).despawn();
+        }
+    }
+}
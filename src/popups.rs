@@ -0,0 +1,239 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::{
+    health::DamageTakenEvent,
+    tint::{sprite_color, TintLayer, Tints},
+    GameState,
+};
+
+// enough for a few dozen krill a second without recycling popups still on screen
+const POOL_SIZE: usize = 64;
+const POPUP_SECONDS: f32 = 0.8;
+// world units per second
+const POPUP_RISE_SPEED: f32 = 20.0;
+// rendered large and scaled down, so the text stays crisp at the camera's zoom
+const POPUP_FONT_SIZE: f32 = 32.0;
+const POPUP_SCALE: f32 = 0.25;
+const POPUP_Z: f32 = 50.0;
+const HIT_FLASH_SECONDS: f32 = 0.12;
+const HIT_FLASH_COLOR: Color = Color::rgb(1.0, 0.3, 0.3);
+
+pub struct PopupsPlugin;
+
+impl Plugin for PopupsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PopupPool>()
+            .init_resource::<HitFlashes>()
+            .add_event::<PopupEvent>()
+            .add_systems(Startup, fill_popup_pool)
+            .add_systems(OnEnter(GameState::MainMenu), clear_popups)
+            .add_systems(
+                Update,
+                (
+                    show_popups,
+                    rise_popups,
+                    start_hit_flashes,
+                    fade_hit_flashes,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Floats `text` up from `position` in the world, then fades it out.
+#[derive(Event, Debug, Clone)]
+pub struct PopupEvent {
+    pub position: Vec2,
+    pub text: String,
+    pub color: Color,
+}
+
+#[derive(Component, Debug)]
+pub struct Popup {
+    pub timer: Timer,
+}
+
+/// Popup text entities, spawned once and reused. When every one is showing, the oldest is
+/// taken over.
+#[derive(Resource, Debug, Default)]
+pub struct PopupPool {
+    pub free: Vec<Entity>,
+    /// oldest first
+    pub showing: VecDeque<Entity>,
+}
+
+#[derive(Debug)]
+pub struct HitFlash {
+    pub entity: Entity,
+    pub timer: Timer,
+}
+
+/// Sprites and meshes currently tinted after taking damage. Kept here rather than as components, since
+/// whatever was hit may be despawned in the same frame.
+#[derive(Resource, Debug, Default)]
+pub struct HitFlashes(pub Vec<HitFlash>);
+
+fn fill_popup_pool(mut commands: Commands, mut pool: ResMut<PopupPool>) {
+    for _ in 0..POOL_SIZE {
+        let popup = commands
+            .spawn((
+                Text2dBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font_size: POPUP_FONT_SIZE,
+                            ..default()
+                        },
+                    ),
+                    transform: Transform::from_scale(Vec3::splat(POPUP_SCALE)),
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                Popup {
+                    timer: Timer::from_seconds(POPUP_SECONDS, TimerMode::Once),
+                },
+            ))
+            .id();
+        pool.free.push(popup);
+    }
+}
+
+fn show_popups(
+    mut popup_events: EventReader<PopupEvent>,
+    mut pool: ResMut<PopupPool>,
+    mut popup_query: Query<(&mut Popup, &mut Text, &mut Transform, &mut Visibility)>,
+) {
+    for event in popup_events.read() {
+        let Some(entity) = pool.free.pop().or_else(|| pool.showing.pop_front()) else {
+            continue;
+        };
+        let Ok((mut popup, mut text, mut transform, mut visibility)) = popup_query.get_mut(entity)
+        else {
+            continue;
+        };
+        popup.timer.reset();
+        text.sections[0].value.clone_from(&event.text);
+        text.sections[0].style.color = event.color;
+        transform.translation = event.position.extend(POPUP_Z);
+        *visibility = Visibility::Visible;
+        pool.showing.push_back(entity);
+    }
+}
+
+fn rise_popups(
+    time: Res<Time>,
+    mut pool: ResMut<PopupPool>,
+    mut popup_query: Query<(&mut Popup, &mut Text, &mut Transform, &mut Visibility)>,
+) {
+    let PopupPool { free, showing } = &mut *pool;
+    showing.retain(|&entity| {
+        let Ok((mut popup, mut text, mut transform, mut visibility)) = popup_query.get_mut(entity)
+        else {
+            return false;
+        };
+        if popup.timer.tick(time.delta()).finished() {
+            *visibility = Visibility::Hidden;
+            free.push(entity);
+            return false;
+        }
+        transform.translation.y += POPUP_RISE_SPEED * time.delta_seconds();
+        text.sections[0]
+            .style
+            .color
+            .set_a(1.0 - popup.timer.percent());
+        true
+    });
+}
+
+fn clear_popups(
+    mut pool: ResMut<PopupPool>,
+    mut flashes: ResMut<HitFlashes>,
+    mut visibility_query: Query<&mut Visibility, With<Popup>>,
+) {
+    let PopupPool { free, showing } = &mut *pool;
+    for entity in showing.drain(..) {
+        if let Ok(mut visibility) = visibility_query.get_mut(entity) {
+            *visibility = Visibility::Hidden;
+        }
+        free.push(entity);
+    }
+    flashes.0.clear();
+}
+
+// Sprites carry their own tint, meshes like the boss head tint through their material.
+fn flash_color<'a>(
+    (sprite, atlas_sprite, material): (
+        Option<Mut<'a, Sprite>>,
+        Option<Mut<'a, TextureAtlasSprite>>,
+        Option<&Handle<ColorMaterial>>,
+    ),
+    materials: &'a mut Assets<ColorMaterial>,
+) -> Option<&'a mut Color> {
+    match sprite_color((sprite, atlas_sprite)) {
+        Some(color) => Some(color.into_inner()),
+        None => materials
+            .get_mut(material?)
+            .map(|material| &mut material.color),
+    }
+}
+
+fn start_hit_flashes(
+    mut damage_taken_events: EventReader<DamageTakenEvent>,
+    mut flashes: ResMut<HitFlashes>,
+    mut tints: ResMut<Tints>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut sprite_query: Query<AnyOf<(&mut Sprite, &mut TextureAtlasSprite, &Handle<ColorMaterial>)>>,
+) {
+    for event in damage_taken_events.read() {
+        if let Some(flash) = flashes
+            .0
+            .iter_mut()
+            .find(|flash| flash.entity == event.target)
+        {
+            flash.timer.reset();
+            continue;
+        }
+        let Some(color) = sprite_query
+            .get_mut(event.target)
+            .ok()
+            .and_then(|sprite| flash_color(sprite, &mut materials))
+        else {
+            continue;
+        };
+        flashes.0.push(HitFlash {
+            entity: event.target,
+            timer: Timer::from_seconds(HIT_FLASH_SECONDS, TimerMode::Once),
+        });
+        tints.add(event.target, TintLayer::HitFlash, HIT_FLASH_COLOR, *color);
+        // keep the alpha, something else may be fading the sprite out
+        *color = HIT_FLASH_COLOR.with_a(color.a());
+    }
+}
+
+fn fade_hit_flashes(
+    time: Res<Time>,
+    mut flashes: ResMut<HitFlashes>,
+    mut tints: ResMut<Tints>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut sprite_query: Query<AnyOf<(&mut Sprite, &mut TextureAtlasSprite, &Handle<ColorMaterial>)>>,
+) {
+    flashes.0.retain_mut(|flash| {
+        if !flash.timer.tick(time.delta()).finished() {
+            return true;
+        }
+        // a sonar reveal may have started, or ended, under the flash
+        let Some(shown) = tints.remove(flash.entity, TintLayer::HitFlash) else {
+            return false;
+        };
+        if let Some(color) = sprite_query
+            .get_mut(flash.entity)
+            .ok()
+            .and_then(|sprite| flash_color(sprite, &mut materials))
+        {
+            *color = shown.with_a(color.a());
+        }
+        false
+    });
+}
@@ -0,0 +1,243 @@
+use bevy::prelude::*;
+use rand::prelude::*;
+
+use crate::{
+    assets::ImageAssets,
+    growth::Growth,
+    in_gameplay,
+    krill::systems::SwarmDensity,
+    map::{BOTTOM_BORDER, LEFT_BORDER, RIGHT_BORDER, TOP_BORDER},
+    player::{
+        input::{read_player_input, PlayerActions},
+        spawn_whale, Dying, Facing, Gulp, Mouth, Stamina, WhaleBundle,
+    },
+    START_RUN,
+};
+
+const RIVAL_COLOR: Color = Color::rgb(0.45, 0.45, 0.55);
+// rivals only bother boosting towards swarms at least this far away
+const RIVAL_BOOST_DISTANCE: f32 = 60.;
+const RIVAL_BOOST_MIN_STAMINA: f32 = 0.5;
+const RIVAL_WOBBLE_FREQUENCY: f32 = 1.3;
+
+pub struct RivalPlugin;
+
+impl Plugin for RivalPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Difficulty>()
+            .init_resource::<Difficulty>()
+            .add_systems(START_RUN, spawn_rivals)
+            .add_systems(
+                Update,
+                // rivals fill in their actions before the whale systems act on this frame's
+                (plan_rivals, drive_rivals)
+                    .chain()
+                    .before(read_player_input)
+                    .run_if(in_gameplay),
+            );
+    }
+}
+
+/// Picked in the main menu; decides how many rivals there are and how sharp they are.
+#[derive(Resource, Clone, Copy, Eq, PartialEq, Debug, Default, Reflect)]
+#[reflect(Resource)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+/// How a difficulty level makes the rival whales behave.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct DifficultyTuning {
+    pub rivals: usize,
+    /// seconds between picking a new swarm and starting to swim for it
+    pub reaction_time: f32,
+    pub replan_interval: f32,
+    /// distance at which a swarm is worth half as much, so low values stick to nearby food
+    pub distance_falloff: f32,
+    /// radians of weave in the rival's swimming line
+    pub wobble: f32,
+    pub boosts: bool,
+}
+
+impl Difficulty {
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Difficulty::Easy => Difficulty::Normal,
+            Difficulty::Normal => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Easy,
+        }
+    }
+
+    pub fn tuning(self) -> DifficultyTuning {
+        match self {
+            Difficulty::Easy => DifficultyTuning {
+                rivals: 1,
+                reaction_time: 1.2,
+                replan_interval: 3.,
+                distance_falloff: 30.,
+                wobble: 0.8,
+                boosts: false,
+            },
+            Difficulty::Normal => DifficultyTuning {
+                rivals: 2,
+                reaction_time: 0.6,
+                replan_interval: 1.5,
+                distance_falloff: 60.,
+                wobble: 0.4,
+                boosts: true,
+            },
+            Difficulty::Hard => DifficultyTuning {
+                rivals: 3,
+                reaction_time: 0.2,
+                replan_interval: 0.75,
+                distance_falloff: 120.,
+                wobble: 0.1,
+                boosts: true,
+            },
+        }
+    }
+}
+
+/// An AI-driven whale.
+#[derive(Clone, Eq, PartialEq, Debug, Default, Component)]
+pub struct Rival;
+
+#[derive(Clone, Debug, Component)]
+pub struct RivalBrain {
+    /// the swarm the rival is swimming for
+    pub target: Option<Vec2>,
+    /// a newly chosen swarm, waiting out the reaction time
+    pub pending: Option<Vec2>,
+    pub replan: Timer,
+    pub reaction: Timer,
+    wobble_phase: f32,
+}
+
+fn spawn_rivals(
+    mut commands: Commands,
+    image_assets: Res<ImageAssets>,
+    difficulty: Res<Difficulty>,
+) {
+    let tuning = difficulty.tuning();
+    let mut rand_gen = thread_rng();
+    let corners = [
+        Vec2::new(LEFT_BORDER, TOP_BORDER),
+        Vec2::new(RIGHT_BORDER, BOTTOM_BORDER),
+        Vec2::new(RIGHT_BORDER, TOP_BORDER),
+        Vec2::new(LEFT_BORDER, BOTTOM_BORDER),
+    ];
+
+    for (index, corner) in corners.into_iter().cycle().take(tuning.rivals).enumerate() {
+        let mut replan = Timer::from_seconds(tuning.replan_interval, TimerMode::Repeating);
+        // plan straight away
+        replan.tick(replan.duration());
+        spawn_whale(
+            &mut commands,
+            (
+                WhaleBundle::new(&image_assets, corner * 0.8, RIVAL_COLOR),
+                Rival,
+                RivalBrain {
+                    target: None,
+                    pending: None,
+                    replan,
+                    reaction: Timer::from_seconds(tuning.reaction_time, TimerMode::Once),
+                    wobble_phase: rand_gen.gen_range(0.0..std::f32::consts::TAU),
+                },
+                Name::new(format!("Rival Whale {}", index + 1)),
+            ),
+        );
+    }
+}
+
+// Picks the swarm with the best mix of size and closeness, then commits to it once the
+// reaction time has passed.
+fn plan_rivals(
+    time: Res<Time>,
+    difficulty: Res<Difficulty>,
+    density: Res<SwarmDensity>,
+    mut rival_query: Query<(&Transform, &mut RivalBrain), (With<Rival>, Without<Dying>)>,
+) {
+    let tuning = difficulty.tuning();
+    for (transform, mut brain) in &mut rival_query {
+        if brain.replan.tick(time.delta()).just_finished() {
+            let position = transform.translation.xy();
+            let best = density
+                .cells()
+                .filter(|(_, count)| *count > 0)
+                .map(|(cell, count)| {
+                    let value =
+                        count as f32 / (1. + cell.distance(position) / tuning.distance_falloff);
+                    (cell, value)
+                })
+                .max_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map(|(cell, _)| cell);
+
+            if best != brain.target {
+                brain.pending = best;
+                brain.reaction.reset();
+            }
+        }
+
+        if brain.pending.is_some() && brain.reaction.tick(time.delta()).just_finished() {
+            brain.target = brain.pending.take();
+        }
+    }
+}
+
+fn drive_rivals(
+    time: Res<Time>,
+    difficulty: Res<Difficulty>,
+    mut rival_query: Query<
+        (
+            &Transform,
+            &RivalBrain,
+            &Facing,
+            &Gulp,
+            &Growth,
+            &Stamina,
+            &Children,
+            &mut PlayerActions,
+        ),
+        (With<Rival>, Without<Dying>),
+    >,
+    mouth_query: Query<&GlobalTransform, With<Mouth>>,
+) {
+    let tuning = difficulty.tuning();
+    for (transform, brain, facing, gulp, growth, stamina, children, mut actions) in &mut rival_query
+    {
+        *actions = PlayerActions::default();
+        let Some(target) = brain.target else {
+            continue;
+        };
+
+        let position = transform.translation.xy();
+        let to_target = target - position;
+        let wobble = (time.elapsed_seconds() * RIVAL_WOBBLE_FREQUENCY + brain.wobble_phase).sin()
+            * tuning.wobble;
+
+        actions.movement = Vec2::from_angle(wobble)
+            .rotate(to_target)
+            .normalize_or_zero();
+        // the cone opens from the mouth, as it does when krill are sucked in
+        let mouth = children
+            .iter()
+            .find_map(|&child| mouth_query.get(child).ok())
+            .map(|mouth_transform| mouth_transform.translation().xy());
+        actions.gulp =
+            mouth.is_some_and(|mouth| gulp.reaches(mouth, facing.0, growth.size, target));
+        actions.boost = tuning.boosts
+            && to_target.length() > RIVAL_BOOST_DISTANCE
+            && stamina.fraction() > RIVAL_BOOST_MIN_STAMINA;
+    }
+}
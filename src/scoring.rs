@@ -0,0 +1,373 @@
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    utils::BoxedFuture,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    boss::systems::BossPhaseEvent,
+    display::systems::DisplayEvent,
+    fish::systems::FishKilledEvent,
+    in_gameplay,
+    krill::systems::SwarmClearedEvent,
+    player::{KrillEatenEvent, Player},
+    popups::PopupEvent,
+    START_RUN,
+};
+
+// the combo callout sits just above the points it came with
+const COMBO_POPUP_OFFSET: f32 = 8.0;
+
+pub struct ScoringPlugin;
+
+impl Plugin for ScoringPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<GameMode>()
+            .register_type::<ScoringRules>()
+            .register_type::<Combo>()
+            .init_resource::<GameMode>()
+            .init_resource::<ScoringRules>()
+            .init_asset::<ScoringRules>()
+            .register_asset_loader(ScoringRulesLoader)
+            .add_event::<ScoreEvent>()
+            .add_systems(Startup, load_scoring_rules)
+            .add_systems(START_RUN, apply_game_mode)
+            .add_systems(
+                Update,
+                (
+                    add_combos,
+                    (
+                        score_krill_eaten,
+                        score_fish_killed,
+                        score_swarm_cleared,
+                        score_boss_phase,
+                    ),
+                    apply_scoring,
+                    decay_combos,
+                )
+                    .chain()
+                    .run_if(in_gameplay),
+            );
+    }
+}
+
+/// Picked in the main menu; decides the scoring rules for the run.
+#[derive(Resource, Clone, Copy, Eq, PartialEq, Debug, Default, Reflect, Serialize, Deserialize)]
+#[reflect(Resource)]
+pub enum GameMode {
+    #[default]
+    Classic,
+    /// cheap points, but combos climb fast and streaks pay big
+    Frenzy,
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 2] = [GameMode::Classic, GameMode::Frenzy];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            GameMode::Classic => "Classic",
+            GameMode::Frenzy => "Frenzy",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            GameMode::Classic => GameMode::Frenzy,
+            GameMode::Frenzy => GameMode::Classic,
+        }
+    }
+
+    /// Where the mode's scoring rules are kept, under `assets`.
+    pub fn rules_path(self) -> String {
+        format!("scoring/{}.scoring.ron", self.name().to_lowercase())
+    }
+}
+
+/// Everything that can earn points.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum ScoreSource {
+    KrillEaten,
+    FishKilled,
+    SwarmCleared,
+    BossPhase,
+}
+
+/// A player did something worth points; `apply_scoring` decides how many.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct ScoreEvent {
+    pub player: Entity,
+    pub source: ScoreSource,
+    /// where it happened, for the points to pop up at
+    pub position: Vec2,
+}
+
+/// Point values and combo tuning for one game mode, read from the mode's rules file.
+#[derive(Resource, Asset, Clone, PartialEq, Debug, Reflect, Deserialize)]
+#[reflect(Resource)]
+pub struct ScoringRules {
+    pub krill_eaten: usize,
+    pub fish_killed: usize,
+    pub swarm_cleared: usize,
+    pub boss_phase: usize,
+    /// seconds a combo survives without scoring again
+    pub combo_window: f32,
+    /// multiplier gained for each link in the chain
+    pub combo_step: f32,
+    pub combo_max: f32,
+    /// `(chain length, bonus)`, paid once as a chain reaches that length
+    pub streak_bonuses: Vec<(usize, usize)>,
+}
+
+impl Default for ScoringRules {
+    fn default() -> Self {
+        Self::for_mode(GameMode::default())
+    }
+}
+
+impl ScoringRules {
+    /// The built-in rules, for when a mode's rules file is missing or broken.
+    pub fn for_mode(mode: GameMode) -> Self {
+        match mode {
+            GameMode::Classic => Self {
+                krill_eaten: 1,
+                fish_killed: 25,
+                swarm_cleared: 50,
+                boss_phase: 200,
+                combo_window: 2.0,
+                combo_step: 0.1,
+                combo_max: 3.0,
+                streak_bonuses: vec![(10, 25), (25, 100), (50, 250)],
+            },
+            GameMode::Frenzy => Self {
+                krill_eaten: 2,
+                fish_killed: 15,
+                swarm_cleared: 100,
+                boss_phase: 150,
+                combo_window: 1.0,
+                combo_step: 0.25,
+                combo_max: 5.0,
+                streak_bonuses: vec![(10, 50), (25, 200), (50, 500), (100, 1500)],
+            },
+        }
+    }
+
+    pub fn points(&self, source: ScoreSource) -> usize {
+        match source {
+            ScoreSource::KrillEaten => self.krill_eaten,
+            ScoreSource::FishKilled => self.fish_killed,
+            ScoreSource::SwarmCleared => self.swarm_cleared,
+            ScoreSource::BossPhase => self.boss_phase,
+        }
+    }
+}
+
+/// A player's run of quick scores. Each link raises the multiplier until the chain lapses.
+#[derive(Component, Clone, PartialEq, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct Combo {
+    pub chain: usize,
+    pub timer: Timer,
+}
+
+impl Combo {
+    pub fn multiplier(&self, rules: &ScoringRules) -> f32 {
+        (1.0 + self.chain as f32 * rules.combo_step).min(rules.combo_max)
+    }
+}
+
+#[derive(Default)]
+pub struct ScoringRulesLoader;
+
+impl AssetLoader for ScoringRulesLoader {
+    type Asset = ScoringRules;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<ScoringRules, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            Ok(ron::de::from_bytes(&bytes)?)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["scoring.ron"]
+    }
+}
+
+/// Each mode's rules file, loaded at startup and read when a run begins.
+#[derive(Resource, Debug)]
+pub struct ScoringRuleFiles(pub Vec<(GameMode, Handle<ScoringRules>)>);
+
+fn load_scoring_rules(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(ScoringRuleFiles(
+        GameMode::ALL
+            .into_iter()
+            .map(|mode| (mode, asset_server.load(mode.rules_path())))
+            .collect(),
+    ));
+}
+
+fn apply_game_mode(
+    mode: Res<GameMode>,
+    files: Res<ScoringRuleFiles>,
+    loaded_rules: Res<Assets<ScoringRules>>,
+    mut rules: ResMut<ScoringRules>,
+) {
+    let loaded = files
+        .0
+        .iter()
+        .find(|(file_mode, _)| *file_mode == *mode)
+        .and_then(|(_, handle)| loaded_rules.get(handle));
+    *rules = match loaded {
+        Some(loaded) => loaded.clone(),
+        None => {
+            warn!(
+                "no scoring rules loaded for {}, using the built-in ones",
+                mode.name()
+            );
+            ScoringRules::for_mode(*mode)
+        }
+    };
+}
+
+fn add_combos(
+    mut commands: Commands,
+    rules: Res<ScoringRules>,
+    player_query: Query<Entity, Added<Player>>,
+) {
+    for player in &player_query {
+        commands.entity(player).insert(Combo {
+            chain: 0,
+            timer: Timer::from_seconds(rules.combo_window, TimerMode::Once),
+        });
+    }
+}
+
+fn score_krill_eaten(
+    mut krill_eaten_events: EventReader<KrillEatenEvent>,
+    mut score_events: EventWriter<ScoreEvent>,
+) {
+    for event in krill_eaten_events.read() {
+        score_events.send(ScoreEvent {
+            player: event.eater,
+            source: ScoreSource::KrillEaten,
+            position: event.position,
+        });
+    }
+}
+
+fn score_fish_killed(
+    mut fish_killed_events: EventReader<FishKilledEvent>,
+    mut score_events: EventWriter<ScoreEvent>,
+) {
+    for event in fish_killed_events.read() {
+        if let Some(killer) = event.killer {
+            score_events.send(ScoreEvent {
+                player: killer,
+                source: ScoreSource::FishKilled,
+                position: event.position,
+            });
+        }
+    }
+}
+
+// credited to whichever player is nearest the swarm when it goes
+fn score_swarm_cleared(
+    mut swarm_cleared_events: EventReader<SwarmClearedEvent>,
+    player_query: Query<(Entity, &Transform), With<Player>>,
+    mut score_events: EventWriter<ScoreEvent>,
+) {
+    for event in swarm_cleared_events.read() {
+        let nearest = player_query.iter().min_by(|(_, a), (_, b)| {
+            let a = a.translation.truncate().distance(event.position);
+            let b = b.translation.truncate().distance(event.position);
+            a.total_cmp(&b)
+        });
+        if let Some((player, _)) = nearest {
+            score_events.send(ScoreEvent {
+                player,
+                source: ScoreSource::SwarmCleared,
+                position: event.position,
+            });
+        }
+    }
+}
+
+// every player shares in pushing the boss into a new phase
+fn score_boss_phase(
+    mut phase_events: EventReader<BossPhaseEvent>,
+    player_query: Query<(Entity, &Transform), With<Player>>,
+    mut score_events: EventWriter<ScoreEvent>,
+) {
+    for _event in phase_events.read() {
+        for (player, transform) in &player_query {
+            score_events.send(ScoreEvent {
+                player,
+                source: ScoreSource::BossPhase,
+                position: transform.translation.xy(),
+            });
+        }
+    }
+}
+
+// Only players carry a `Combo`, so rivals and fish scoring for themselves are ignored here.
+fn apply_scoring(
+    rules: Res<ScoringRules>,
+    mut score_events: EventReader<ScoreEvent>,
+    mut combo_query: Query<&mut Combo>,
+    mut display_events: EventWriter<DisplayEvent>,
+    mut popup_events: EventWriter<PopupEvent>,
+) {
+    for event in score_events.read() {
+        let Ok(mut combo) = combo_query.get_mut(event.player) else {
+            continue;
+        };
+        let previous_multiplier = combo.multiplier(&rules);
+        combo.chain += 1;
+        combo.timer.reset();
+
+        let base = rules.points(event.source) as f32;
+        let streak_bonus = rules
+            .streak_bonuses
+            .iter()
+            .find(|(chain, _)| *chain == combo.chain)
+            .map_or(0, |(_, bonus)| *bonus);
+        let multiplier = combo.multiplier(&rules);
+        let points = (base * multiplier).round() as usize + streak_bonus;
+        display_events.send(DisplayEvent {
+            points,
+            player: Some(event.player),
+        });
+
+        popup_events.send(PopupEvent {
+            position: event.position,
+            text: format!("+{points}"),
+            color: Color::WHITE,
+        });
+        // call out each whole step the multiplier climbs
+        if multiplier.floor() > previous_multiplier.floor() {
+            popup_events.send(PopupEvent {
+                position: event.position + Vec2::Y * COMBO_POPUP_OFFSET,
+                text: format!("x{} combo", multiplier.floor()),
+                color: Color::ORANGE,
+            });
+        }
+    }
+}
+
+fn decay_combos(time: Res<Time>, mut combo_query: Query<&mut Combo>) {
+    for mut combo in &mut combo_query {
+        if combo.timer.tick(time.delta()).just_finished() {
+            combo.chain = 0;
+        }
+    }
+}
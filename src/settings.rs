@@ -0,0 +1,594 @@
+use bevy::{
+    audio::{GlobalVolume, VolumeLevel},
+    prelude::*,
+    window::{PrimaryWindow, WindowMode, WindowResolution},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    despawn::StateScoped,
+    krill::systems::KRILL_ENTITYS_STARTING_AMT,
+    player::input::{InputBindings, KeyBindings},
+    storage, GameState,
+};
+
+const SAVE_NAME: &str = "settings";
+const VOLUME_STEP: f32 = 0.1;
+pub const RESOLUTIONS: [(u32, u32); 4] = [(1280, 720), (1600, 900), (1920, 1080), (2560, 1440)];
+const TITLE_FONT_SIZE: f32 = 50.0;
+const ROW_FONT_SIZE: f32 = 18.0;
+
+// (english, spanish); anything missing stays in english
+const SPANISH: &[(&str, &str)] = &[
+    ("Settings", "Ajustes"),
+    ("Master volume", "Volumen general"),
+    ("Window", "Ventana"),
+    ("Resolution", "Resolución"),
+    ("Camera shake", "Temblor de cámara"),
+    ("Krill", "Kril"),
+    ("Language", "Idioma"),
+    ("On", "Sí"),
+    ("Off", "No"),
+    ("Windowed", "En ventana"),
+    ("Borderless", "Sin bordes"),
+    ("Fullscreen", "Pantalla completa"),
+    ("Low", "Bajo"),
+    ("Medium", "Medio"),
+    ("High", "Alto"),
+    ("Left keyboard", "Teclado izquierdo"),
+    ("Right keyboard", "Teclado derecho"),
+    ("Up", "Arriba"),
+    ("Down", "Abajo"),
+    ("Left", "Izquierda"),
+    ("Right", "Derecha"),
+    ("Boost", "Impulso"),
+    ("Gulp", "Tragar"),
+    ("Sonar", "Sonar"),
+    ("Fire", "Disparar"),
+    ("press a key...", "pulsa una tecla..."),
+    (
+        "Up/Down to choose, Left/Right to change, Enter to rebind, Esc to go back",
+        "Arriba/Abajo para elegir, Izquierda/Derecha para cambiar, Enter para reasignar, Esc para volver",
+    ),
+    (
+        "Enter to dive in, S for settings, L for high scores, Esc to quit",
+        "Enter para sumergirte, S para ajustes, L para récords, Esc para salir",
+    ),
+    ("Mode", "Modo"),
+    ("Tab to change", "Tab para cambiar"),
+    ("Difficulty", "Dificultad"),
+    ("D to change", "D para cambiar"),
+    ("Easy", "Fácil"),
+    ("Hard", "Difícil"),
+    ("Paused", "Pausa"),
+    (
+        "Esc to resume, M for the main menu",
+        "Esc para continuar, M para el menú principal",
+    ),
+    ("Game Over", "Fin de la partida"),
+    ("Cthulhu Sleeps Again", "Cthulhu duerme de nuevo"),
+    ("Enter for the main menu", "Enter para el menú principal"),
+];
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        // `main` loads these before the window is made; the rest is applied here, before the
+        // plugins that read them are built
+        let settings = app
+            .world
+            .get_resource_or_insert_with(Settings::load)
+            .clone();
+        app.register_type::<Settings>()
+            .insert_resource(settings.bindings)
+            .insert_resource(GlobalVolume::new(settings.master_volume))
+            .init_resource::<SettingsMenu>()
+            .add_systems(OnEnter(GameState::Settings), show_settings)
+            .add_systems(OnExit(GameState::Settings), save_settings)
+            .add_systems(
+                Update,
+                (
+                    (change_settings, update_settings_text)
+                        .chain()
+                        .run_if(in_state(GameState::Settings)),
+                    apply_settings.run_if(resource_changed::<Settings>()),
+                ),
+            );
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default, Reflect, Serialize, Deserialize)]
+pub enum KrillQuality {
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl KrillQuality {
+    /// How many krill a run starts with.
+    pub fn krill_count(self) -> usize {
+        let medium = KRILL_ENTITYS_STARTING_AMT as usize;
+        match self {
+            KrillQuality::Low => medium / 2,
+            KrillQuality::Medium => medium,
+            KrillQuality::High => medium * 3 / 2,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            KrillQuality::Low => "Low",
+            KrillQuality::Medium => "Medium",
+            KrillQuality::High => "High",
+        }
+    }
+
+    fn step(self, forward: bool) -> Self {
+        match (self, forward) {
+            (KrillQuality::Low, true) | (KrillQuality::High, false) => KrillQuality::Medium,
+            (KrillQuality::Medium, true) => KrillQuality::High,
+            (KrillQuality::Medium, false) => KrillQuality::Low,
+            (quality, _) => quality,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default, Reflect, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    English,
+    Spanish,
+}
+
+impl Language {
+    fn name(self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::Spanish => "Español",
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            Language::English => Language::Spanish,
+            Language::Spanish => Language::English,
+        }
+    }
+
+    /// `english` in this language, if there's a translation for it.
+    pub fn translate(self, english: &'static str) -> &'static str {
+        let table = match self {
+            Language::English => return english,
+            Language::Spanish => SPANISH,
+        };
+        table
+            .iter()
+            .find(|(key, _)| *key == english)
+            .map_or(english, |(_, translated)| translated)
+    }
+}
+
+/// Everything the player can change from the settings screen, saved between launches.
+#[derive(Resource, Clone, PartialEq, Debug, Reflect, Serialize, Deserialize)]
+#[reflect(Resource)]
+#[serde(default)]
+pub struct Settings {
+    pub master_volume: f32,
+    pub bindings: InputBindings,
+    pub window_mode: WindowMode,
+    pub resolution: (u32, u32),
+    pub camera_shake: bool,
+    pub krill_quality: KrillQuality,
+    pub language: Language,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            master_volume: 1.0,
+            bindings: InputBindings::default(),
+            window_mode: WindowMode::Windowed,
+            resolution: RESOLUTIONS[0],
+            camera_shake: true,
+            krill_quality: KrillQuality::default(),
+            language: Language::default(),
+        }
+    }
+}
+
+impl Settings {
+    /// Reads the saved settings, falling back to the defaults for anything missing.
+    pub fn load() -> Self {
+        let Some(saved) = storage::read(SAVE_NAME) else {
+            return Self::default();
+        };
+        ron::from_str(&saved).unwrap_or_else(|error| {
+            warn!("ignoring corrupted settings: {error}");
+            Self::default()
+        })
+    }
+
+    pub fn save(&self) {
+        match ron::to_string(self) {
+            Ok(serialized) => {
+                if let Err(error) = storage::write(SAVE_NAME, &serialized) {
+                    warn!("could not save settings: {error}");
+                }
+            }
+            Err(error) => warn!("could not serialize settings: {error}"),
+        }
+    }
+
+    /// The primary window as these settings ask for it.
+    pub fn window(&self) -> Window {
+        Window {
+            title: "Krilling With Cthulhu".to_string(),
+            mode: self.window_mode,
+            resolution: WindowResolution::new(self.resolution.0 as f32, self.resolution.1 as f32),
+            ..default()
+        }
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum KeyAction {
+    Up,
+    Down,
+    Left,
+    Right,
+    Boost,
+    Gulp,
+    Sonar,
+    Fire,
+}
+
+impl KeyAction {
+    const ALL: [KeyAction; 8] = [
+        KeyAction::Up,
+        KeyAction::Down,
+        KeyAction::Left,
+        KeyAction::Right,
+        KeyAction::Boost,
+        KeyAction::Gulp,
+        KeyAction::Sonar,
+        KeyAction::Fire,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            KeyAction::Up => "Up",
+            KeyAction::Down => "Down",
+            KeyAction::Left => "Left",
+            KeyAction::Right => "Right",
+            KeyAction::Boost => "Boost",
+            KeyAction::Gulp => "Gulp",
+            KeyAction::Sonar => "Sonar",
+            KeyAction::Fire => "Fire",
+        }
+    }
+
+    fn key(self, keys: &mut KeyBindings) -> &mut KeyCode {
+        match self {
+            KeyAction::Up => &mut keys.up,
+            KeyAction::Down => &mut keys.down,
+            KeyAction::Left => &mut keys.left,
+            KeyAction::Right => &mut keys.right,
+            KeyAction::Boost => &mut keys.boost,
+            KeyAction::Gulp => &mut keys.gulp,
+            KeyAction::Sonar => &mut keys.sonar,
+            KeyAction::Fire => &mut keys.fire,
+        }
+    }
+}
+
+/// One line of the settings screen.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum SettingRow {
+    MasterVolume,
+    WindowMode,
+    Resolution,
+    CameraShake,
+    KrillQuality,
+    Language,
+    /// a key on the left (`false`) or right (`true`) half of the keyboard
+    Key(bool, KeyAction),
+}
+
+impl SettingRow {
+    fn all() -> Vec<SettingRow> {
+        let mut rows = vec![
+            SettingRow::MasterVolume,
+            SettingRow::WindowMode,
+            SettingRow::Resolution,
+            SettingRow::CameraShake,
+            SettingRow::KrillQuality,
+            SettingRow::Language,
+        ];
+        for right in [false, true] {
+            rows.extend(KeyAction::ALL.map(|action| SettingRow::Key(right, action)));
+        }
+        rows
+    }
+
+    fn label(self, settings: &Settings) -> String {
+        let language = settings.language;
+        let tr = |text| language.translate(text);
+        let volume = |volume: f32| format!("{:.0}%", volume * 100.0);
+        match self {
+            SettingRow::MasterVolume => format!(
+                "{}: {}",
+                tr("Master volume"),
+                volume(settings.master_volume)
+            ),
+            SettingRow::WindowMode => format!(
+                "{}: {}",
+                tr("Window"),
+                tr(window_mode_name(settings.window_mode))
+            ),
+            SettingRow::Resolution => format!(
+                "{}: {}x{}",
+                tr("Resolution"),
+                settings.resolution.0,
+                settings.resolution.1
+            ),
+            SettingRow::CameraShake => format!(
+                "{}: {}",
+                tr("Camera shake"),
+                tr(if settings.camera_shake { "On" } else { "Off" })
+            ),
+            SettingRow::KrillQuality => format!(
+                "{}: {} ({})",
+                tr("Krill"),
+                tr(settings.krill_quality.name()),
+                settings.krill_quality.krill_count()
+            ),
+            SettingRow::Language => format!("{}: {}", tr("Language"), settings.language.name()),
+            SettingRow::Key(right, action) => {
+                let mut bindings = settings.bindings;
+                let (side, keys) = if right {
+                    ("Right keyboard", &mut bindings.keyboard_right)
+                } else {
+                    ("Left keyboard", &mut bindings.keyboard_left)
+                };
+                format!("{} {}: {:?}", tr(side), tr(action.name()), action.key(keys))
+            }
+        }
+    }
+
+    // Left and right step through the options; volume clamps, everything else wraps.
+    fn change(self, settings: &mut Settings, forward: bool) {
+        let step = if forward { VOLUME_STEP } else { -VOLUME_STEP };
+        let nudge = |volume: &mut f32| *volume = (*volume + step).clamp(0.0, 1.0);
+        match self {
+            SettingRow::MasterVolume => nudge(&mut settings.master_volume),
+            SettingRow::WindowMode => {
+                settings.window_mode = match (settings.window_mode, forward) {
+                    (WindowMode::Windowed, true) | (WindowMode::Fullscreen, false) => {
+                        WindowMode::BorderlessFullscreen
+                    }
+                    (WindowMode::BorderlessFullscreen, true) => WindowMode::Fullscreen,
+                    _ => WindowMode::Windowed,
+                };
+            }
+            SettingRow::Resolution => {
+                let current = RESOLUTIONS
+                    .iter()
+                    .position(|resolution| *resolution == settings.resolution)
+                    .unwrap_or(0);
+                let count = RESOLUTIONS.len();
+                let next = if forward {
+                    (current + 1) % count
+                } else {
+                    (current + count - 1) % count
+                };
+                settings.resolution = RESOLUTIONS[next];
+            }
+            SettingRow::CameraShake => settings.camera_shake = !settings.camera_shake,
+            SettingRow::KrillQuality => {
+                settings.krill_quality = settings.krill_quality.step(forward);
+            }
+            SettingRow::Language => settings.language = settings.language.next(),
+            SettingRow::Key(..) => {}
+        }
+    }
+}
+
+fn window_mode_name(mode: WindowMode) -> &'static str {
+    match mode {
+        WindowMode::Windowed => "Windowed",
+        WindowMode::BorderlessFullscreen => "Borderless",
+        _ => "Fullscreen",
+    }
+}
+
+/// Where the cursor is on the settings screen, and whether it's waiting for a key to bind.
+#[derive(Resource, Debug, Default)]
+pub struct SettingsMenu {
+    pub selected: usize,
+    pub rebinding: bool,
+}
+
+/// The text for row `index` of `SettingRow::all()`.
+#[derive(Component, Debug)]
+pub struct SettingText(pub usize);
+
+#[derive(Component, Debug)]
+pub struct SettingsTitle;
+
+#[derive(Component, Debug)]
+pub struct SettingsHint;
+
+fn show_settings(mut commands: Commands, settings: Res<Settings>, mut menu: ResMut<SettingsMenu>) {
+    *menu = SettingsMenu::default();
+    let language = settings.language;
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(2.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.05, 0.8).into(),
+                ..default()
+            },
+            StateScoped(GameState::Settings),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    language.translate("Settings"),
+                    TextStyle {
+                        font_size: TITLE_FONT_SIZE,
+                        color: Color::AQUAMARINE,
+                        ..default()
+                    },
+                ),
+                SettingsTitle,
+            ));
+            for index in 0..SettingRow::all().len() {
+                parent.spawn((
+                    TextBundle::from_section(
+                        "",
+                        TextStyle {
+                            font_size: ROW_FONT_SIZE,
+                            ..default()
+                        },
+                    ),
+                    SettingText(index),
+                ));
+            }
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: ROW_FONT_SIZE,
+                        ..default()
+                    },
+                ),
+                SettingsHint,
+            ));
+        });
+}
+
+fn change_settings(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut settings: ResMut<Settings>,
+    mut menu: ResMut<SettingsMenu>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let rows = SettingRow::all();
+    let row = rows[menu.selected];
+
+    if menu.rebinding {
+        let Some(&key) = keyboard_input.get_just_pressed().next() else {
+            return;
+        };
+        // Esc gives up on rebinding instead of being bound
+        if key != KeyCode::Escape {
+            if let SettingRow::Key(right, action) = row {
+                let keys = if right {
+                    &mut settings.bindings.keyboard_right
+                } else {
+                    &mut settings.bindings.keyboard_left
+                };
+                *action.key(keys) = key;
+            }
+        }
+        menu.rebinding = false;
+        return;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        next_state.set(GameState::MainMenu);
+    } else if keyboard_input.just_pressed(KeyCode::Up) {
+        menu.selected = (menu.selected + rows.len() - 1) % rows.len();
+    } else if keyboard_input.just_pressed(KeyCode::Down) {
+        menu.selected = (menu.selected + 1) % rows.len();
+    } else if keyboard_input.just_pressed(KeyCode::Left) {
+        row.change(&mut settings, false);
+    } else if keyboard_input.just_pressed(KeyCode::Right) {
+        row.change(&mut settings, true);
+    } else if keyboard_input.just_pressed(KeyCode::Return) {
+        if let SettingRow::Key(..) = row {
+            menu.rebinding = true;
+        } else {
+            row.change(&mut settings, true);
+        }
+    }
+}
+
+fn update_settings_text(
+    settings: Res<Settings>,
+    menu: Res<SettingsMenu>,
+    mut row_query: Query<(&mut Text, &SettingText)>,
+    mut title_query: Query<&mut Text, (With<SettingsTitle>, Without<SettingText>)>,
+    mut hint_query: Query<
+        &mut Text,
+        (
+            With<SettingsHint>,
+            Without<SettingText>,
+            Without<SettingsTitle>,
+        ),
+    >,
+) {
+    if !settings.is_changed() && !menu.is_changed() {
+        return;
+    }
+    let rows = SettingRow::all();
+    let language = settings.language;
+    for (mut text, SettingText(index)) in &mut row_query {
+        let selected = *index == menu.selected;
+        let section = &mut text.sections[0];
+        section.value = if selected && menu.rebinding {
+            language.translate("press a key...").to_string()
+        } else {
+            rows[*index].label(&settings)
+        };
+        section.style.color = if selected { Color::GOLD } else { Color::WHITE };
+    }
+    // the language may have just changed under the title and hint
+    for mut title in &mut title_query {
+        title.sections[0].value = language.translate("Settings").to_string();
+    }
+    for mut hint in &mut hint_query {
+        hint.sections[0].value = language
+            .translate("Up/Down to choose, Left/Right to change, Enter to rebind, Esc to go back")
+            .to_string();
+    }
+}
+
+fn save_settings(settings: Res<Settings>) {
+    settings.save();
+}
+
+fn apply_settings(
+    settings: Res<Settings>,
+    mut bindings: ResMut<InputBindings>,
+    mut global_volume: ResMut<GlobalVolume>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    bindings.set_if_neq(settings.bindings);
+    global_volume.volume = VolumeLevel::new(settings.master_volume);
+
+    let Ok(mut window) = window_query.get_single_mut() else {
+        return;
+    };
+    if window.mode != settings.window_mode {
+        window.mode = settings.window_mode;
+    }
+    let (width, height) = settings.resolution;
+    // logical, like the size `Settings::window` opens the window at
+    let (width, height) = (width as f32, height as f32);
+    if window.mode == WindowMode::Windowed
+        && (window.resolution.width() != width || window.resolution.height() != height)
+    {
+        window.resolution.set(width, height);
+    }
+}
@@ -0,0 +1,199 @@
+use bevy::prelude::*;
+
+use crate::{
+    despawn::{Lifetime, RunScoped},
+    in_gameplay,
+    map::DarkRegions,
+    player::{input::PlayerActions, Dying, Whale},
+    tint::{sprite_color, TintLayer, Tints},
+};
+
+const SONAR_COOLDOWN: f32 = 3.0;
+const SONAR_SPEED: f32 = 120.0;
+const SONAR_MAX_RADIUS: f32 = 160.0;
+// thickness of the ring front that reveals and startles what it passes
+const SONAR_BAND: f32 = 8.0;
+const REVEAL_DURATION: f32 = 2.0;
+const REVEAL_COLOR: Color = Color::rgb(0.5, 1.0, 1.0);
+
+pub struct SonarPlugin;
+
+impl Plugin for SonarPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Sonar>().add_systems(
+            Update,
+            (
+                emit_sonar,
+                expand_pulses,
+                reveal_in_pulse,
+                fade_reveal,
+                conceal_in_dark,
+                draw_pulses,
+            )
+                .chain()
+                .run_if(in_gameplay),
+        );
+    }
+}
+
+/// Lets a whale emit echolocation pulses.
+#[derive(Clone, PartialEq, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct Sonar {
+    pub cooldown: Timer,
+}
+
+impl Default for Sonar {
+    fn default() -> Self {
+        let mut cooldown = Timer::from_seconds(SONAR_COOLDOWN, TimerMode::Once);
+        cooldown.tick(cooldown.duration());
+        Self { cooldown }
+    }
+}
+
+/// An expanding ring, centred where it was emitted.
+#[derive(Clone, PartialEq, Debug, Component)]
+pub struct SonarPulse {
+    pub origin: Vec2,
+    pub radius: f32,
+}
+
+impl SonarPulse {
+    /// Whether `point` is on the ring's leading edge this frame.
+    pub fn touches(&self, point: Vec2) -> bool {
+        (point.distance(self.origin) - self.radius).abs() < SONAR_BAND
+    }
+}
+
+/// Hidden in dark regions of the map unless `Revealed`.
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default, Component)]
+pub struct Concealable;
+
+/// Recently touched by a pulse: shown even in the dark, and tinted.
+#[derive(Clone, Debug, Component)]
+pub struct Revealed {
+    timer: Timer,
+}
+
+fn emit_sonar(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut player_query: Query<
+        (&PlayerActions, &Transform, &mut Sonar),
+        (With<Whale>, Without<Dying>),
+    >,
+) {
+    for (actions, transform, mut sonar) in &mut player_query {
+        if !sonar.cooldown.tick(time.delta()).finished() || !actions.sonar {
+            continue;
+        }
+        sonar.cooldown.reset();
+        commands.spawn((
+            SonarPulse {
+                origin: transform.translation.xy(),
+                radius: 0.0,
+            },
+            Lifetime::from_seconds(SONAR_MAX_RADIUS / SONAR_SPEED),
+            RunScoped,
+            Name::new("Sonar Pulse"),
+        ));
+    }
+}
+
+fn expand_pulses(time: Res<Time>, mut pulse_query: Query<&mut SonarPulse>) {
+    for mut pulse in &mut pulse_query {
+        pulse.radius += SONAR_SPEED * time.delta_seconds();
+    }
+}
+
+fn reveal_in_pulse(
+    mut commands: Commands,
+    mut tints: ResMut<Tints>,
+    pulse_query: Query<&SonarPulse>,
+    mut concealable_query: Query<
+        (
+            Entity,
+            &Transform,
+            AnyOf<(&mut Sprite, &mut TextureAtlasSprite)>,
+            Option<&mut Revealed>,
+        ),
+        With<Concealable>,
+    >,
+) {
+    // each entity is checked against every pulse at once, as `Revealed` only lands at the end
+    // of the frame and a second pulse would otherwise reveal it again
+    for (entity, transform, sprite, revealed) in &mut concealable_query {
+        let position = transform.translation.xy();
+        if !pulse_query.iter().any(|pulse| pulse.touches(position)) {
+            continue;
+        }
+        match revealed {
+            Some(mut revealed) => revealed.timer.reset(),
+            None => {
+                let Some(mut color) = sprite_color(sprite) else {
+                    continue;
+                };
+                commands.entity(entity).insert(Revealed {
+                    timer: Timer::from_seconds(REVEAL_DURATION, TimerMode::Once),
+                });
+                tints.add(entity, TintLayer::Reveal, REVEAL_COLOR, *color);
+                *color = REVEAL_COLOR.with_a(color.a());
+            }
+        }
+    }
+}
+
+fn fade_reveal(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut tints: ResMut<Tints>,
+    mut revealed_query: Query<(
+        Entity,
+        &mut Revealed,
+        AnyOf<(&mut Sprite, &mut TextureAtlasSprite)>,
+    )>,
+) {
+    for (entity, mut revealed, sprite) in &mut revealed_query {
+        if revealed.timer.tick(time.delta()).finished() {
+            // a hit flash may still be showing over the reveal
+            if let (Some(shown), Some(mut color)) = (
+                tints.remove(entity, TintLayer::Reveal),
+                sprite_color(sprite),
+            ) {
+                *color = shown.with_a(color.a());
+            }
+            commands.entity(entity).remove::<Revealed>();
+        }
+    }
+}
+
+fn conceal_in_dark(
+    dark_regions: Res<DarkRegions>,
+    mut concealable_query: Query<(&Transform, &mut Visibility, Has<Revealed>), With<Concealable>>,
+) {
+    for (transform, mut visibility, revealed) in &mut concealable_query {
+        let hidden = !revealed && dark_regions.contains(transform.translation.xy());
+        let target = if hidden {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+        // avoid tripping change detection on every krill every frame
+        if *visibility != target {
+            *visibility = target;
+        }
+    }
+}
+
+fn draw_pulses(mut gizmos: Gizmos, pulse_query: Query<&SonarPulse>) {
+    for pulse in &pulse_query {
+        let fade = 1.0 - pulse.radius / SONAR_MAX_RADIUS;
+        gizmos
+            .circle_2d(
+                pulse.origin,
+                pulse.radius,
+                Color::rgba(0.5, 1.0, 1.0, fade.max(0.0)),
+            )
+            .segments(64);
+    }
+}
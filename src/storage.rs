@@ -0,0 +1,77 @@
+//! Small saves kept between launches: RON files in the user's data directory on native, and
+//! `localStorage` in the browser.
+
+#[cfg(not(target_arch = "wasm32"))]
+const SAVE_DIR: &str = "KrillingWithCthulhu";
+#[cfg(target_arch = "wasm32")]
+const STORAGE_KEY_PREFIX: &str = "krilling_with_cthulhu";
+
+#[cfg(not(target_arch = "wasm32"))]
+mod platform {
+    use std::{env, fs, path::PathBuf};
+
+    use super::SAVE_DIR;
+
+    // where each platform keeps per-user application data
+    fn data_dir() -> Option<PathBuf> {
+        if cfg!(target_os = "windows") {
+            env::var_os("APPDATA").map(PathBuf::from)
+        } else if cfg!(target_os = "macos") {
+            env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"))
+        } else {
+            env::var_os("XDG_DATA_HOME").map(PathBuf::from).or_else(|| {
+                env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share"))
+            })
+        }
+    }
+
+    fn save_path(name: &str) -> Option<PathBuf> {
+        data_dir().map(|dir| dir.join(SAVE_DIR).join(format!("{name}.ron")))
+    }
+
+    pub fn read(name: &str) -> Option<String> {
+        fs::read_to_string(save_path(name)?).ok()
+    }
+
+    pub fn write(name: &str, contents: &str) -> Result<(), String> {
+        let path = save_path(name).ok_or("no data directory")?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|error| error.to_string())?;
+        }
+        fs::write(path, contents).map_err(|error| error.to_string())
+    }
+
+    pub fn now() -> u64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs())
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod platform {
+    use super::STORAGE_KEY_PREFIX;
+
+    fn local_storage() -> Option<web_sys::Storage> {
+        web_sys::window()?.local_storage().ok()?
+    }
+
+    pub fn read(name: &str) -> Option<String> {
+        local_storage()?
+            .get_item(&format!("{STORAGE_KEY_PREFIX}.{name}"))
+            .ok()?
+    }
+
+    pub fn write(name: &str, contents: &str) -> Result<(), String> {
+        local_storage()
+            .ok_or("localStorage is unavailable")?
+            .set_item(&format!("{STORAGE_KEY_PREFIX}.{name}"), contents)
+            .map_err(|error| format!("{error:?}"))
+    }
+
+    pub fn now() -> u64 {
+        (js_sys::Date::now() / 1000.0) as u64
+    }
+}
+
+pub use platform::{now, read, write};
@@ -0,0 +1,260 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    health::Hazard,
+    in_gameplay,
+    krill::systems::{Acceleration, Krill},
+    map::{BOTTOM_BORDER, TOP_BORDER},
+    player::{Dying, Whale},
+    sonar::Concealable,
+};
+
+const SEGMENT_LENGTH: f32 = 8.;
+const SEGMENT_RADIUS: f32 = 2.;
+const SEGMENT_DENSITY: f32 = 5.;
+const SEGMENT_DAMPING: f32 = 2.;
+const SEGMENT_COLOR: Color = Color::rgb(0.35, 0.15, 0.3);
+// tentacles ignore each other and themselves, so a chain can curl without snagging
+const TENTACLE_COLLISION_GROUP: Group = Group::GROUP_3;
+const TENTACLE_DAMAGE: f32 = 8.;
+// krill touching a moving segment are shoved along with it
+const TENTACLE_SWEEP_MAG: f32 = 20.;
+// the walls' inner faces, where level tentacles take root
+const FLOOR_SURFACE: f32 = BOTTOM_BORDER - 10.;
+const CEILING_SURFACE: f32 = TOP_BORDER + 10.;
+/// `(root, direction it grows in, segments)`
+const LEVEL_TENTACLES: [(Vec2, Vec2, usize); 3] = [
+    (Vec2::new(-70., FLOOR_SURFACE), Vec2::Y, 6),
+    (Vec2::new(80., FLOOR_SURFACE), Vec2::Y, 5),
+    (Vec2::new(40., CEILING_SURFACE), Vec2::NEG_Y, 5),
+];
+
+pub struct TentaclePlugin;
+
+impl Plugin for TentaclePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<TentacleHazard>()
+            .add_systems(Startup, spawn_level_tentacles)
+            .add_systems(
+                Update,
+                ((seek_whales, drive_tentacles).chain(), sweep_krill).run_if(in_gameplay),
+            );
+    }
+}
+
+/// A hazard made of a chain of jointed bodies, rooted in place. It waves about on its own and
+/// lashes towards any whale that swims within reach.
+#[derive(Clone, PartialEq, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct TentacleHazard {
+    /// the direction the tentacle grows in while relaxed
+    pub direction: Vec2,
+    pub segments: Vec<Entity>,
+    /// how far each joint swings, in radians
+    pub wave_amplitude: f32,
+    pub wave_speed: f32,
+    /// phase step from one joint to the next, so the wave travels along the tentacle
+    pub wave_length: f32,
+    pub reach: f32,
+    /// the most the whole tentacle will bend towards a target, in radians
+    pub max_bend: f32,
+    pub stiffness: f32,
+    pub damping: f32,
+    pub target: Option<Vec2>,
+}
+
+impl Default for TentacleHazard {
+    fn default() -> Self {
+        Self {
+            direction: Vec2::Y,
+            segments: Vec::new(),
+            wave_amplitude: 0.25,
+            wave_speed: 2.,
+            wave_length: 0.6,
+            reach: 60.,
+            max_bend: 1.4,
+            stiffness: 400.,
+            damping: 40.,
+            target: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default, Component)]
+pub struct HazardTentacleSegment;
+
+/// Spawns a tentacle growing from `root` in `direction`, one segment per joint.
+pub fn spawn_tentacle(
+    commands: &mut Commands,
+    root: Vec2,
+    direction: Vec2,
+    segments: usize,
+    mut hazard: TentacleHazard,
+) -> Entity {
+    let direction = direction.normalize();
+    // segments are long along their y axis
+    let rotation = Quat::from_rotation_arc_2d(Vec2::Y, direction);
+    let anchor = commands
+        .spawn((
+            Name::new("Tentacle Hazard"),
+            RigidBody::Fixed,
+            TransformBundle::from_transform(Transform {
+                translation: root.extend(0.),
+                rotation,
+                ..default()
+            }),
+        ))
+        .id();
+
+    let mut parent = anchor;
+    for index in 0..segments {
+        let center = root + direction * (index as f32 + 0.5) * SEGMENT_LENGTH;
+        // the anchor joins at its origin, every segment after at the previous one's far end
+        let parent_anchor = if index == 0 {
+            Vec2::ZERO
+        } else {
+            Vec2::Y * SEGMENT_LENGTH / 2.
+        };
+        let joint = RevoluteJointBuilder::new()
+            .local_anchor1(parent_anchor)
+            .local_anchor2(Vec2::NEG_Y * SEGMENT_LENGTH / 2.)
+            .motor_position(0., hazard.stiffness, hazard.damping);
+
+        parent = commands
+            .spawn((
+                Name::new("Tentacle Segment"),
+                HazardTentacleSegment,
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: SEGMENT_COLOR,
+                        custom_size: Some(Vec2::new(SEGMENT_RADIUS * 2., SEGMENT_LENGTH)),
+                        ..default()
+                    },
+                    transform: Transform {
+                        translation: center.extend(0.),
+                        rotation,
+                        ..default()
+                    },
+                    ..default()
+                },
+                RigidBody::Dynamic,
+                Collider::capsule_y(SEGMENT_LENGTH / 2. - SEGMENT_RADIUS, SEGMENT_RADIUS),
+                ColliderMassProperties::Density(SEGMENT_DENSITY),
+                CollisionGroups::new(
+                    TENTACLE_COLLISION_GROUP,
+                    Group::complement(TENTACLE_COLLISION_GROUP),
+                ),
+                Damping {
+                    linear_damping: SEGMENT_DAMPING,
+                    angular_damping: SEGMENT_DAMPING,
+                },
+                Velocity::zero(),
+                ImpulseJoint::new(parent, joint),
+                Hazard {
+                    damage: TENTACLE_DAMAGE,
+                },
+                Concealable,
+            ))
+            .id();
+        hazard.segments.push(parent);
+    }
+
+    hazard.direction = direction;
+    commands.entity(anchor).insert(hazard);
+    anchor
+}
+
+fn spawn_level_tentacles(mut commands: Commands) {
+    for (root, direction, segments) in LEVEL_TENTACLES {
+        spawn_tentacle(
+            &mut commands,
+            root,
+            direction,
+            segments,
+            TentacleHazard::default(),
+        );
+    }
+}
+
+fn seek_whales(
+    mut tentacle_query: Query<(&mut TentacleHazard, &Transform)>,
+    whale_query: Query<&Transform, (With<Whale>, Without<Dying>)>,
+) {
+    for (mut tentacle, transform) in &mut tentacle_query {
+        let root = transform.translation.truncate();
+        tentacle.target = whale_query
+            .iter()
+            .map(|whale| whale.translation.truncate())
+            .filter(|whale| whale.distance(root) <= tentacle.reach)
+            .min_by(|a, b| a.distance(root).total_cmp(&b.distance(root)));
+    }
+}
+
+// Each joint's motor is aimed at its share of the bend towards the target, plus a travelling
+// wave so the tentacle never sits still.
+fn drive_tentacles(
+    time: Res<Time>,
+    tentacle_query: Query<(&TentacleHazard, &Transform)>,
+    mut joint_query: Query<&mut ImpulseJoint, With<HazardTentacleSegment>>,
+) {
+    for (tentacle, transform) in &tentacle_query {
+        let root = transform.translation.truncate();
+        let bend = tentacle
+            .target
+            .map(|target| {
+                tentacle
+                    .direction
+                    .angle_between(target - root)
+                    .clamp(-tentacle.max_bend, tentacle.max_bend)
+            })
+            .unwrap_or(0.);
+        // a tentacle lashing at something waves less
+        let amplitude = if tentacle.target.is_some() {
+            tentacle.wave_amplitude * 0.5
+        } else {
+            tentacle.wave_amplitude
+        };
+        let joints = tentacle.segments.len() as f32;
+
+        for (index, segment) in tentacle.segments.iter().enumerate() {
+            let Ok(mut joint) = joint_query.get_mut(*segment) else {
+                continue;
+            };
+            let wave = (time.elapsed_seconds() * tentacle.wave_speed
+                - index as f32 * tentacle.wave_length)
+                .sin();
+            if let Some(revolute) = joint.data.as_revolute_mut() {
+                revolute.set_motor_position(
+                    bend / joints + wave * amplitude,
+                    tentacle.stiffness,
+                    tentacle.damping,
+                );
+            }
+        }
+    }
+}
+
+fn sweep_krill(
+    rapier_context: Res<RapierContext>,
+    segment_query: Query<(Entity, &Velocity), With<HazardTentacleSegment>>,
+    mut krill_query: Query<&mut Acceleration, With<Krill>>,
+) {
+    for (segment, velocity) in &segment_query {
+        let touching = rapier_context
+            .contacts_with(segment)
+            .filter(|contact| contact.has_any_active_contacts())
+            .map(|contact| {
+                if contact.collider1() == segment {
+                    contact.collider2()
+                } else {
+                    contact.collider1()
+                }
+            });
+        for other in touching {
+            if let Ok(mut acceleration) = krill_query.get_mut(other) {
+                acceleration.apply(velocity.linvel * TENTACLE_SWEEP_MAG);
+            }
+        }
+    }
+}
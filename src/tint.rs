@@ -0,0 +1,129 @@
+use bevy::{ecs::entity::Entities, prelude::*, utils::HashMap};
+
+pub struct TintPlugin;
+
+impl Plugin for TintPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Tints>()
+            .add_systems(Last, forget_despawned_tints);
+    }
+}
+
+/// Something that tints an entity for a while.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum TintLayer {
+    HitFlash,
+    Reveal,
+}
+
+#[derive(Debug)]
+struct TintStack {
+    /// the entity's own colour, shown again once every layer is gone
+    base: Color,
+    /// oldest first, the newest is the one showing
+    layers: Vec<(TintLayer, Color)>,
+}
+
+/// Temporary tints over entities' own colours. Overlapping tints, like a hit flash during a
+/// sonar reveal, hand back to each other and then to the entity's own colour, rather than to
+/// whatever happened to be showing when they started. Kept here rather than as components,
+/// since whatever is tinted may be despawned in the same frame.
+#[derive(Resource, Debug, Default)]
+pub struct Tints(HashMap<Entity, TintStack>);
+
+impl Tints {
+    /// Puts `color` on top of the entity's tints. `current` is taken as its own colour if
+    /// nothing else is tinting it yet.
+    pub fn add(&mut self, entity: Entity, layer: TintLayer, color: Color, current: Color) {
+        let stack = self.0.entry(entity).or_insert_with(|| TintStack {
+            base: current,
+            layers: Vec::new(),
+        });
+        stack.layers.retain(|(other, _)| *other != layer);
+        stack.layers.push((layer, color));
+    }
+
+    /// Takes `layer` off the entity's tints, returning the colour to show now, or `None` if the
+    /// layer wasn't there.
+    pub fn remove(&mut self, entity: Entity, layer: TintLayer) -> Option<Color> {
+        let stack = self.0.get_mut(&entity)?;
+        let index = stack.layers.iter().position(|(other, _)| *other == layer)?;
+        stack.layers.remove(index);
+        if let Some(&(_, color)) = stack.layers.last() {
+            return Some(color);
+        }
+        self.0.remove(&entity).map(|stack| stack.base)
+    }
+}
+
+/// The tint of whichever kind of sprite an entity has.
+pub fn sprite_color<'a>(
+    (sprite, atlas_sprite): (Option<Mut<'a, Sprite>>, Option<Mut<'a, TextureAtlasSprite>>),
+) -> Option<Mut<'a, Color>> {
+    match (sprite, atlas_sprite) {
+        (Some(sprite), _) => Some(sprite.map_unchanged(|sprite| &mut sprite.color)),
+        (None, Some(sprite)) => Some(sprite.map_unchanged(|sprite| &mut sprite.color)),
+        (None, None) => None,
+    }
+}
+
+// A revealed krill that gets eaten never has its reveal wear off.
+fn forget_despawned_tints(entities: &Entities, mut tints: ResMut<Tints>) {
+    tints.0.retain(|&entity, _| entities.contains(entity));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OWN: Color = Color::WHITE;
+    const FLASH: Color = Color::RED;
+    const REVEAL: Color = Color::CYAN;
+
+    #[test]
+    fn reveal_during_a_flash_goes_back_to_the_own_colour() {
+        let entity = Entity::from_raw(1);
+        let mut tints = Tints::default();
+        tints.add(entity, TintLayer::HitFlash, FLASH, OWN);
+        // the pulse sees the flash showing, but mustn't take it for the entity's colour
+        tints.add(entity, TintLayer::Reveal, REVEAL, FLASH);
+
+        assert_eq!(tints.remove(entity, TintLayer::HitFlash), Some(REVEAL));
+        assert_eq!(tints.remove(entity, TintLayer::Reveal), Some(OWN));
+        assert!(tints.0.is_empty());
+    }
+
+    #[test]
+    fn flash_during_a_reveal_goes_back_to_the_own_colour() {
+        let entity = Entity::from_raw(1);
+        let mut tints = Tints::default();
+        tints.add(entity, TintLayer::Reveal, REVEAL, OWN);
+        tints.add(entity, TintLayer::HitFlash, FLASH, REVEAL);
+
+        // the reveal wears off first, and the flash keeps showing until it ends
+        assert_eq!(tints.remove(entity, TintLayer::Reveal), Some(FLASH));
+        assert_eq!(tints.remove(entity, TintLayer::HitFlash), Some(OWN));
+        assert!(tints.0.is_empty());
+    }
+
+    #[test]
+    fn flash_ending_inside_a_reveal_shows_the_reveal() {
+        let entity = Entity::from_raw(1);
+        let mut tints = Tints::default();
+        tints.add(entity, TintLayer::Reveal, REVEAL, OWN);
+        tints.add(entity, TintLayer::HitFlash, FLASH, REVEAL);
+
+        assert_eq!(tints.remove(entity, TintLayer::HitFlash), Some(REVEAL));
+        assert_eq!(tints.remove(entity, TintLayer::Reveal), Some(OWN));
+    }
+
+    #[test]
+    fn removing_a_missing_layer_changes_nothing() {
+        let entity = Entity::from_raw(1);
+        let mut tints = Tints::default();
+        tints.add(entity, TintLayer::Reveal, REVEAL, OWN);
+
+        assert_eq!(tints.remove(entity, TintLayer::HitFlash), None);
+        assert_eq!(tints.remove(entity, TintLayer::Reveal), Some(OWN));
+    }
+}